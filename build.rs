@@ -22,7 +22,7 @@ fn main() {
     let wordlist_paths = fs::read_dir(Path::new("src/mnemonic/wordlists")).unwrap();
     for path in wordlist_paths {
         let (lang, words) = read_wordlist(path).unwrap();
-        let dest_path = Path::new(&out_dir).join(lang.clone()).with_extension(&"rs");
+        let dest_path = Path::new(&out_dir).join(lang.clone()).with_extension("rs");
         let mut dest_file = File::create(&dest_path).unwrap();
         writeln!(
             dest_file,
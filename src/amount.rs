@@ -0,0 +1,187 @@
+use crate::{
+    result::Result,
+    traits::txn_fee::{dc_to_bones, DC_PER_USD},
+};
+use helium_api::Hnt;
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use std::{fmt, str::FromStr};
+
+/// The number of decimals in an HNT amount
+const HNT_DECIMALS: u32 = 8;
+/// The number of decimals in a USD amount, one DC being 0.00001 USD
+const USD_DECIMALS: u32 = 5;
+
+/// An amount of HNT given in one of the supported units. A plain
+/// number or a number followed by "hnt" is in HNT, "bones" gives a
+/// whole number of bones, "dc" a whole number of Data Credits and a
+/// "$" prefix or "usd" suffix a value in USD. DC and USD amounts are
+/// converted to HNT at the current oracle price.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Amount {
+    Bones(u64),
+    Dc(u64),
+    Usd(Decimal),
+}
+
+impl Amount {
+    /// The amount in bones. The oracle price is only requested for DC
+    /// and USD amounts.
+    pub fn to_bones<F>(&self, oracle_price: F) -> Result<u64>
+    where
+        F: FnOnce() -> Result<u64>,
+    {
+        match self {
+            Self::Bones(bones) => Ok(*bones),
+            Self::Dc(dc) => dc_to_bones(*dc, oracle_price()?),
+            Self::Usd(usd) => dc_to_bones(usd_to_dc(*usd)?, oracle_price()?),
+        }
+    }
+
+    /// The number of DC a DC or USD amount is for
+    pub fn to_dc(&self) -> Result<Option<u64>> {
+        match self {
+            Self::Bones(_) => Ok(None),
+            Self::Dc(dc) => Ok(Some(*dc)),
+            Self::Usd(usd) => Ok(Some(usd_to_dc(*usd)?)),
+        }
+    }
+}
+
+/// Convert a USD value to the number of DC it buys, rounded up to a
+/// whole DC.
+pub fn usd_to_dc(usd: Decimal) -> Result<u64> {
+    (usd * Decimal::from(DC_PER_USD))
+        .ceil()
+        .to_u64()
+        .ok_or_else(|| format!("Invalid USD amount {}", usd).into())
+}
+
+/// Parse a plain decimal number with at most the given number of
+/// decimals. Signs, exponents and thousands separators are rejected.
+fn parse_decimal(s: &str, decimals: u32, unit: &str) -> Result<Decimal> {
+    let invalid = || format!("Invalid {} amount \"{}\"", unit, s);
+    if s.is_empty()
+        || s.starts_with('.')
+        || s.ends_with('.')
+        || !s.chars().all(|c| c.is_ascii_digit() || c == '.')
+        || s.matches('.').count() > 1
+    {
+        return Err(invalid().into());
+    }
+    let value = Decimal::from_str(s).map_err(|_| invalid())?;
+    if value.scale() > decimals {
+        return Err(format!(
+            "Invalid {} amount \"{}\", at most {} decimals are allowed",
+            unit, s, decimals
+        )
+        .into());
+    }
+    Ok(value)
+}
+
+fn parse_integer(s: &str, unit: &str) -> Result<u64> {
+    if s.is_empty() || !s.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("Invalid {} amount \"{}\", expected a whole number", unit, s).into());
+    }
+    s.parse()
+        .map_err(|_| format!("Invalid {} amount \"{}\"", unit, s).into())
+}
+
+impl FromStr for Amount {
+    type Err = Box<dyn std::error::Error>;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let lower = s.trim().to_lowercase();
+        if let Some(usd) = lower.strip_prefix('$') {
+            return Ok(Self::Usd(parse_decimal(usd.trim(), USD_DECIMALS, "USD")?));
+        }
+        let split = lower
+            .find(|c: char| c.is_ascii_alphabetic())
+            .unwrap_or(lower.len());
+        let (number, unit) = (lower[..split].trim(), &lower[split..]);
+        match unit {
+            "" | "hnt" => {
+                let hnt = parse_decimal(number, HNT_DECIMALS, "HNT")?;
+                let bones = (hnt * Decimal::from(10u64.pow(HNT_DECIMALS)))
+                    .to_u64()
+                    .ok_or_else(|| format!("Invalid HNT amount \"{}\"", s))?;
+                Ok(Self::Bones(bones))
+            }
+            "bones" | "bone" => Ok(Self::Bones(parse_integer(number, "bones")?)),
+            "dc" => Ok(Self::Dc(parse_integer(number, "DC")?)),
+            "usd" => Ok(Self::Usd(parse_decimal(number, USD_DECIMALS, "USD")?)),
+            _ => Err(format!(
+                "Invalid amount \"{}\", expected a unit of hnt, bones, dc or usd",
+                s
+            )
+            .into()),
+        }
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Bones(bones) => write!(f, "{} HNT", Hnt::from_bones(*bones)),
+            Self::Dc(dc) => write!(f, "{} DC", dc),
+            Self::Usd(usd) => write!(f, "${}", usd),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Amount {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_units() {
+        assert_eq!(parse("1.5"), Amount::Bones(150_000_000));
+        assert_eq!(parse("1.5hnt"), Amount::Bones(150_000_000));
+        assert_eq!(parse("1.5 HNT"), Amount::Bones(150_000_000));
+        assert_eq!(parse("0.00000001"), Amount::Bones(1));
+        assert_eq!(parse("150000000bones"), Amount::Bones(150_000_000));
+        assert_eq!(parse("5000dc"), Amount::Dc(5000));
+        assert_eq!(parse("$12.50"), Amount::Usd(Decimal::new(1250, 2)));
+        assert_eq!(parse("12.5usd"), Amount::Usd(Decimal::new(125, 1)));
+    }
+
+    #[test]
+    fn reject_ambiguous() {
+        for s in &[
+            "",
+            "hnt",
+            "1.5dc",
+            "1.5bones",
+            "0.000000001",
+            "$0.000001",
+            "-1",
+            "1e8",
+            "1,000",
+            "1.2.3",
+            ".5",
+            "1.5hnt dc",
+            "$1hnt",
+            "5 credits",
+        ] {
+            assert!(s.parse::<Amount>().is_err(), "{} should be rejected", s);
+        }
+    }
+
+    #[test]
+    fn convert_to_bones() {
+        // At $1 per HNT one DC is 1/100_000 HNT
+        let price = || Ok(100_000_000);
+        assert_eq!(parse("5000dc").to_bones(price).unwrap(), 5_000_000);
+        assert_eq!(parse("$12.50").to_bones(price).unwrap(), 1_250_000_000);
+        assert_eq!(
+            parse("1hnt")
+                .to_bones(|| Err("no oracle price needed".into()))
+                .unwrap(),
+            100_000_000
+        );
+    }
+}
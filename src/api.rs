@@ -0,0 +1,190 @@
+use crate::result::Result;
+use serde_derive::Deserialize;
+use std::{
+    fmt, thread,
+    time::{Duration, Instant},
+};
+
+/// The default timeout for API requests
+pub const DEFAULT_TIMEOUT: u64 = 120;
+/// The interval between checks of a pending transaction
+const PENDING_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A client for Helium API endpoints that are not covered by the
+/// helium_api crate.
+pub struct Client {
+    base_url: String,
+    client: reqwest::Client,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PendingTxnState {
+    Received,
+    Pending,
+    Cleared,
+    Failed,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PendingTxn {
+    pub hash: String,
+    #[serde(rename = "type")]
+    pub txn_type: Option<String>,
+    pub status: PendingTxnState,
+    pub failed_reason: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    /// The submitted transaction as reported by the API
+    #[serde(default)]
+    pub txn: serde_json::Value,
+}
+
+impl PendingTxn {
+    /// Whether the transaction has either cleared or failed.
+    pub fn is_done(&self) -> bool {
+        matches!(
+            self.status,
+            PendingTxnState::Cleared | PendingTxnState::Failed
+        )
+    }
+
+    /// The nonce of the transaction, if the API reports one
+    pub fn nonce(&self) -> Option<u64> {
+        self.txn["nonce"].as_u64()
+    }
+}
+
+impl Client {
+    /// Create a new client using a given base URL and a default
+    /// timeout. The library will use absoluate paths based on this
+    /// base_url.
+    pub fn new_with_base_url(base_url: String) -> Self {
+        let client = reqwest::Client::builder()
+            .gzip(true)
+            .timeout(Duration::from_secs(DEFAULT_TIMEOUT))
+            .build()
+            .unwrap();
+        Self { base_url, client }
+    }
+
+    /// Fetch the current oracle price in 1/100_000_000 USD per HNT.
+    pub fn get_oracle_price(&self) -> Result<u64> {
+        let request_url = format!("{}/oracle/prices/current", self.base_url);
+        let response: serde_json::Value = self
+            .client
+            .get(&request_url)
+            .send()?
+            .error_for_status()?
+            .json()?;
+        response["data"]["price"]
+            .as_u64()
+            .ok_or_else(|| "Invalid oracle price from API".into())
+    }
+
+    /// Fetch the status of a submitted transaction. Returns None if the
+    /// API does not (yet) know about the transaction.
+    pub fn get_pending_txn(&self, hash: &str) -> Result<Option<PendingTxn>> {
+        let request_url = format!("{}/pending_transactions/{}", self.base_url, hash);
+        let response = self.client.get(&request_url).send()?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let json: serde_json::Value = response.error_for_status()?.json()?;
+        let data = match &json["data"] {
+            serde_json::Value::Array(txns) => txns.first().cloned(),
+            serde_json::Value::Null => None,
+            txn => Some(txn.clone()),
+        };
+        match data {
+            Some(txn) => Ok(Some(serde_json::from_value(txn)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Fetch the transactions submitted by the given account that the
+    /// API still tracks as pending or failed.
+    pub fn get_account_pending_txns(&self, address: &str) -> Result<Vec<PendingTxn>> {
+        let request_url = format!(
+            "{}/accounts/{}/pending_transactions",
+            self.base_url, address
+        );
+        let json: serde_json::Value = self
+            .client
+            .get(&request_url)
+            .send()?
+            .error_for_status()?
+            .json()?;
+        match &json["data"] {
+            serde_json::Value::Null => Ok(vec![]),
+            data => Ok(serde_json::from_value(data.clone())?),
+        }
+    }
+
+    /// Fetch the transactions of the given comma separated types that
+    /// involve the given account, newest first, back to the given unix
+    /// time.
+    pub fn get_account_activity(
+        &self,
+        address: &str,
+        filter_types: &str,
+        since: u64,
+    ) -> Result<Vec<serde_json::Value>> {
+        let request_url = format!("{}/accounts/{}/activity", self.base_url, address);
+        let mut txns = vec![];
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut request = self
+                .client
+                .get(&request_url)
+                .query(&[("filter_types", filter_types)]);
+            if let Some(cursor) = &cursor {
+                request = request.query(&[("cursor", cursor)]);
+            }
+            let json: serde_json::Value = request.send()?.error_for_status()?.json()?;
+            let mut done = false;
+            for txn in json["data"].as_array().into_iter().flatten() {
+                match txn["time"].as_u64() {
+                    Some(time) if time < since => done = true,
+                    _ => txns.push(txn.clone()),
+                }
+            }
+            cursor = json["cursor"].as_str().map(str::to_string);
+            if done || cursor.is_none() {
+                return Ok(txns);
+            }
+        }
+    }
+
+    /// Poll the status of a submitted transaction until it clears or
+    /// fails, or until the given timeout expires.
+    pub fn wait_for_txn(&self, hash: &str, timeout: Duration) -> Result<PendingTxn> {
+        let start = Instant::now();
+        loop {
+            if let Some(txn) = self.get_pending_txn(hash)? {
+                if txn.is_done() {
+                    return Ok(txn);
+                }
+            }
+            if start.elapsed() + PENDING_POLL_INTERVAL > timeout {
+                return Err(format!("Timed out waiting for transaction {}", hash).into());
+            }
+            thread::sleep(PENDING_POLL_INTERVAL);
+        }
+    }
+}
+
+impl fmt::Display for PendingTxnState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            PendingTxnState::Received => "received",
+            PendingTxnState::Pending => "pending",
+            PendingTxnState::Cleared => "cleared",
+            PendingTxnState::Failed => "failed",
+            PendingTxnState::Unknown => "unknown",
+        };
+        f.write_str(s)
+    }
+}
@@ -0,0 +1,170 @@
+use crate::{
+    cmd::{
+        fees::{dc_to_usd, oracle_price_usd},
+        print_footer, print_json, print_table, status_str, total_amount, FeeCost, OutputFormat,
+    },
+    keypair::PubKeyBin,
+    memo::{Memo, MemoFormat},
+    result::Result,
+    traits::{B58, B64},
+};
+use helium_api::{BlockchainTxn, BlockchainTxnTokenBurnV1, Hnt, PendingTxnStatus};
+use prettytable::{format, Table};
+use serde_derive::Deserialize;
+use serde_json::json;
+use std::path::PathBuf;
+
+/// A single row in a burns file
+pub struct BatchBurn {
+    pub row: usize,
+    pub payee: PubKeyBin,
+    pub dc: u64,
+    pub memo: u64,
+}
+
+#[derive(Deserialize)]
+struct CsvRecord {
+    address: String,
+    dc: String,
+    #[serde(default)]
+    memo: Option<String>,
+}
+
+/// Read and validate all burns in the given CSV file. All invalid rows
+/// are reported at once.
+pub fn read_burns(path: &PathBuf, memo_format: MemoFormat) -> Result<Vec<BatchBurn>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_path(path)?;
+    let headers = reader.headers()?.clone();
+    let mut burns = vec![];
+    let mut errors = vec![];
+    for result in reader.records() {
+        let record = result?;
+        let row = record.position().map_or(0, |p| p.line() as usize);
+        let csv_record: CsvRecord = record.deserialize(Some(&headers))?;
+        match parse_record(&csv_record, memo_format) {
+            Ok((payee, dc, memo)) => burns.push(BatchBurn {
+                row,
+                payee,
+                dc,
+                memo,
+            }),
+            Err(err) => errors.push(format!("row {}: {}", row, err)),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(format!("Invalid burns:\n  {}", errors.join("\n  ")).into());
+    }
+    if burns.is_empty() {
+        return Err("No burns found".into());
+    }
+    Ok(burns)
+}
+
+fn parse_record(record: &CsvRecord, memo_format: MemoFormat) -> Result<(PubKeyBin, u64, u64)> {
+    let payee = PubKeyBin::from_b58(&record.address)
+        .map_err(|_| format!("invalid address \"{}\"", record.address))?;
+    let dc: u64 = record
+        .dc
+        .parse()
+        .map_err(|_| format!("invalid dc amount \"{}\"", record.dc))?;
+    if dc == 0 {
+        return Err("dc amount must be greater than zero".into());
+    }
+    let memo = match &record.memo {
+        Some(memo) if !memo.is_empty() => Memo::parse(memo, memo_format)?.0,
+        _ => 0,
+    };
+    Ok((payee, dc, memo))
+}
+
+pub fn print_batch(
+    burns: &[BatchBurn],
+    txns: &[(
+        BlockchainTxnTokenBurnV1,
+        BlockchainTxn,
+        Option<PendingTxnStatus>,
+    )],
+    oracle_price: u64,
+    cost: &FeeCost,
+    format: OutputFormat,
+) -> Result {
+    let total_dc = total_amount(burns.iter().map(|burn| burn.dc))?;
+    let total_bones = total_amount(txns.iter().map(|(txn, _, _)| txn.amount))?;
+    let txn_hash = |index: usize| -> Result<Option<String>> {
+        match txns.get(index) {
+            Some((_, envelope, status)) => Ok(Some(status_str(envelope, status)?)),
+            None => Ok(None),
+        }
+    };
+    match format {
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+            table.set_titles(row![
+                "Row", "Payee", "DC", "Amount", "Memo", "Nonce", "Fee", "Hash"
+            ]);
+            for (index, burn) in burns.iter().enumerate() {
+                let txn = txns.get(index).map(|(txn, _, _)| txn);
+                table.add_row(row![
+                    burn.row,
+                    burn.payee.to_b58()?,
+                    burn.dc,
+                    txn.map_or_else(String::new, |txn| Hnt::from_bones(txn.amount).to_string()),
+                    burn.memo.to_b64()?,
+                    txn.map_or_else(String::new, |txn| txn.nonce.to_string()),
+                    txn.map_or_else(String::new, |txn| txn.fee.to_string()),
+                    txn_hash(index)?.unwrap_or_else(|| "not submitted".to_string())
+                ]);
+            }
+            print_table(&table)?;
+
+            ptable!(
+                ["Key", "Value"],
+                ["Total DC", total_dc],
+                ["Total Value (USD)", dc_to_usd(total_dc)],
+                ["Total Amount", Hnt::from_bones(total_bones)],
+                ["Oracle Price (USD)", oracle_price_usd(oracle_price)],
+                ["Total Fee (DC)", cost.dc],
+                ["Total Fee (HNT)", cost.hnt],
+                ["Implicit Burn", cost.implicit_burn]
+            );
+            if txns.iter().all(|(_, _, status)| status.is_none()) {
+                print_footer(&None)?;
+            }
+            Ok(())
+        }
+        OutputFormat::Json => {
+            let mut json_burns = Vec::with_capacity(burns.len());
+            for (index, burn) in burns.iter().enumerate() {
+                let txn = txns.get(index);
+                json_burns.push(json!({
+                    "row": burn.row,
+                    "payee": burn.payee.to_b58()?,
+                    "dc": burn.dc,
+                    "amount": txn.map(|(txn, _, _)| Hnt::from_bones(txn.amount)),
+                    "memo": burn.memo.to_b64()?,
+                    "nonce": txn.map(|(txn, _, _)| txn.nonce),
+                    "fee": txn.map(|(txn, _, _)| txn.fee),
+                    "hash": txn_hash(index)?,
+                    "txn": match txn {
+                        Some((_, envelope, _)) => Some(envelope.to_b64()?),
+                        None => None,
+                    },
+                }));
+            }
+            let table = json!({
+                "burns": json_burns,
+                "total_dc": total_dc,
+                "total_usd": dc_to_usd(total_dc),
+                "total_amount": Hnt::from_bones(total_bones),
+                "oracle_price": oracle_price_usd(oracle_price),
+                "total_fee": cost.dc,
+                "total_fee_hnt": cost.hnt,
+                "implicit_burn": cost.implicit_burn,
+            });
+            print_json(&table)
+        }
+    }
+}
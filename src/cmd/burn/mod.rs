@@ -0,0 +1,367 @@
+use crate::{
+    amount::Amount,
+    api,
+    cmd::{
+        api_url,
+        fees::{dc_to_usd, oracle_price_usd},
+        get_nonce, get_password, get_txn_fees, load_wallet, print_footer, print_json, print_table,
+        sign_txn, status_json, status_str, submit_txn, total_amount, update_journal, CommitOpts,
+        FeeCost, Opts, OutputFormat,
+    },
+    journal::JournalStatus,
+    keypair::PubKeyBin,
+    memo::{Memo, MemoFormat},
+    nonce::NonceKind,
+    result::Result,
+    traits::{txn_fee::dc_to_bones, TxnEnvelope, TxnFee, TxnFeeConfig, B58, B64},
+};
+use helium_api::{BlockchainTxn, BlockchainTxnTokenBurnV1, Client, Hnt, PendingTxnStatus};
+use prettytable::Table;
+use rust_decimal::Decimal;
+use serde_json::json;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+mod batch;
+
+#[derive(Debug, StructOpt)]
+/// Burn HNT to Data Credits (DC) from this wallet to given payees wallet.
+pub struct Cmd {
+    /// Account address to send the resulting DC to.
+    #[structopt(long, required_unless = "from-file")]
+    payee: Option<String>,
+
+    /// Memo field to include, in the format given by --memo-format
+    #[structopt(long)]
+    memo: Option<String>,
+
+    /// Format of the memo: base64 encoded, a decimal or hexadecimal
+    /// number, or up to 8 ASCII characters
+    #[structopt(long,
+                possible_values = &["base64", "decimal", "hex", "ascii"],
+                case_insensitive = true,
+                default_value = "base64")]
+    memo_format: MemoFormat,
+
+    /// Amount to burn to DC, in HNT unless a unit of bones, dc or usd
+    /// is given. An amount in dc or usd is the same as --dc or --usd,
+    /// so neither can be combined with it
+    #[structopt(long,
+                required_unless_one = &["dc", "usd", "from-file"],
+                conflicts_with_all = &["dc", "usd"])]
+    amount: Option<Amount>,
+
+    /// Burn the HNT needed for the given number of DC at the current
+    /// oracle price
+    #[structopt(long, conflicts_with_all = &["amount", "usd"])]
+    dc: Option<u64>,
+
+    /// Burn the HNT needed for the given value in USD of DC at the
+    /// current oracle price
+    #[structopt(long, conflicts_with_all = &["amount", "dc"])]
+    usd: Option<Decimal>,
+
+    /// Read burns from a CSV file with "address" and "dc" columns and
+    /// an optional "memo" column in the format given by
+    /// --memo-format. Every row is burned in its own transaction, using
+    /// consecutive nonces.
+    #[structopt(
+        long = "from-file",
+        name = "from-file",
+        conflicts_with_all = &["payee", "memo", "amount", "dc", "usd"]
+    )]
+    from_file: Option<PathBuf>,
+
+    /// Manually set the nonce to use for the transaction
+    #[structopt(long)]
+    pub(crate) nonce: Option<u64>,
+
+    #[structopt(flatten)]
+    commit: CommitOpts,
+}
+
+/// The conversion of a DC amount to the HNT burned for it
+#[derive(Debug, Clone, Copy)]
+pub struct Conversion {
+    pub dc: u64,
+    pub oracle_price: u64,
+}
+
+impl Conversion {
+    /// Calculate the bones needed to burn for the given number of DC
+    /// at the current oracle price, rounded up.
+    pub fn get(dc: u64) -> Result<(u64, Self)> {
+        if dc == 0 {
+            return Err("DC amount must be greater than zero".into());
+        }
+        let oracle_price = api::Client::new_with_base_url(api_url()).get_oracle_price()?;
+        let bones = dc_to_bones(dc, oracle_price)?;
+        Ok((bones, Self { dc, oracle_price }))
+    }
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        if let Some(path) = &self.from_file {
+            return self.run_batch(path, opts);
+        }
+        let password = get_password(false)?;
+        let wallet = load_wallet(opts.files)?;
+
+        let client = Client::new_with_base_url(api_url());
+
+        let keypair = wallet.decrypt(password.as_bytes())?;
+        let fee_config = get_txn_fees(&client, &opts.vars_file)?;
+        let (amount, conversion) = self.burn_amount()?;
+        let nonce = get_nonce(
+            &client,
+            &keypair.pubkey_bin(),
+            NonceKind::Hnt,
+            self.nonce,
+            self.commit.commit,
+        )?;
+
+        let mut txn = mk_burn_txn(
+            keypair.pubkey_bin(),
+            self.payee()?,
+            amount,
+            self.memo()?,
+            nonce.nonce,
+            &fee_config,
+        )?;
+        txn.signature = sign_txn(&txn, &keypair)?;
+        let envelope = txn.in_envelope();
+        let cost = self.commit.validate(&client, &txn)?;
+        let status = self.commit.submit(&client, &envelope, Some(nonce))?;
+        print_txn(&txn, &envelope, &conversion, &cost, &status, opts.format)?;
+        self.commit.maybe_wait(&status, opts.format)
+    }
+
+    fn run_batch(&self, path: &PathBuf, opts: Opts) -> Result {
+        let burns = batch::read_burns(path, self.memo_format)?;
+
+        let password = get_password(false)?;
+        let wallet = load_wallet(opts.files)?;
+
+        let client = Client::new_with_base_url(api_url());
+
+        let keypair = wallet.decrypt(password.as_bytes())?;
+        let fee_config = get_txn_fees(&client, &opts.vars_file)?;
+        let oracle_price = api::Client::new_with_base_url(api_url()).get_oracle_price()?;
+
+        let mut first_nonce = Some(get_nonce(
+            &client,
+            &keypair.pubkey_bin(),
+            NonceKind::Hnt,
+            self.nonce,
+            self.commit.commit,
+        )?);
+        let base_nonce = first_nonce.as_ref().map_or(0, |nonce| nonce.nonce);
+        let mut unsigned = Vec::with_capacity(burns.len());
+        for (index, burn) in burns.iter().enumerate() {
+            unsigned.push(mk_burn_txn(
+                keypair.pubkey_bin(),
+                burn.payee,
+                dc_to_bones(burn.dc, oracle_price)?,
+                burn.memo,
+                base_nonce + index as u64,
+                &fee_config,
+            )?);
+        }
+        // All burns are validated as a single burn of the combined
+        // amount and fee before any of the transactions is submitted
+        let combined = BlockchainTxnTokenBurnV1 {
+            payer: keypair.pubkey_bin().into(),
+            payee: keypair.pubkey_bin().into(),
+            amount: total_amount(unsigned.iter().map(|txn| txn.amount))?,
+            memo: 0,
+            nonce: base_nonce,
+            fee: total_amount(unsigned.iter().map(|txn| txn.fee))?,
+            signature: vec![],
+        };
+        let cost = self.commit.validate(&client, &combined)?;
+        self.commit.confirm(&combined.in_envelope())?;
+
+        let mut txns = vec![];
+        let mut failure = None;
+        for (index, mut txn) in unsigned.into_iter().enumerate() {
+            // Reserve each following nonce only when its transaction
+            // is about to be submitted
+            let nonce = match first_nonce.take() {
+                Some(nonce) => nonce,
+                None => get_nonce(
+                    &client,
+                    &keypair.pubkey_bin(),
+                    NonceKind::Hnt,
+                    Some(base_nonce + index as u64),
+                    self.commit.commit,
+                )?,
+            };
+            txn.signature = sign_txn(&txn, &keypair)?;
+            let envelope = txn.in_envelope();
+            let status = if self.commit.commit {
+                match submit_txn(&client, &envelope, Some(nonce)) {
+                    Ok(status) => Some(status),
+                    Err(err) => {
+                        failure = Some(err);
+                        break;
+                    }
+                }
+            } else {
+                update_journal(&envelope, JournalStatus::Preview, None);
+                None
+            };
+            txns.push((txn, envelope, status));
+        }
+
+        batch::print_batch(&burns, &txns, oracle_price, &cost, opts.format)?;
+        if let Some(err) = failure {
+            return Err(format!(
+                "Failed to submit burn transaction {}, later transactions were not submitted: {}",
+                txns.len() + 1,
+                err
+            )
+            .into());
+        }
+        for (_, _, status) in &txns {
+            self.commit.maybe_wait(status, opts.format)?;
+        }
+        Ok(())
+    }
+
+    fn payee(&self) -> Result<PubKeyBin> {
+        match &self.payee {
+            Some(payee) => PubKeyBin::from_b58(payee),
+            None => Err("A payee is required".into()),
+        }
+    }
+
+    fn memo(&self) -> Result<u64> {
+        match &self.memo {
+            None => Ok(0),
+            Some(s) => Ok(Memo::parse(s, self.memo_format)?.0),
+        }
+    }
+
+    /// The number of bones to burn, and the conversion used to
+    /// calculate them when burning for a DC or USD amount.
+    fn burn_amount(&self) -> Result<(u64, Option<Conversion>)> {
+        let amount = match (self.amount, self.dc, self.usd) {
+            (Some(amount), _, _) => amount,
+            (_, Some(dc), _) => Amount::Dc(dc),
+            (_, _, Some(usd)) => Amount::Usd(usd),
+            _ => return Err("One of --amount, --dc or --usd is required".into()),
+        };
+        let dc = match amount {
+            Amount::Bones(bones) => return Ok((bones, None)),
+            amount => amount.to_dc()?.unwrap_or_default(),
+        };
+        let (bones, conversion) = Conversion::get(dc)?;
+        Ok((bones, Some(conversion)))
+    }
+
+    /// Construct the unsigned burn transaction for the given payer.
+    pub(crate) fn mk_txn(
+        &self,
+        payer: PubKeyBin,
+        nonce: u64,
+        fee_config: &TxnFeeConfig,
+    ) -> Result<BlockchainTxnTokenBurnV1> {
+        if self.from_file.is_some() {
+            return Err("Burns from a file can only be submitted directly".into());
+        }
+        let (amount, _) = self.burn_amount()?;
+        mk_burn_txn(
+            payer,
+            self.payee()?,
+            amount,
+            self.memo()?,
+            nonce,
+            fee_config,
+        )
+    }
+}
+
+fn mk_burn_txn(
+    payer: PubKeyBin,
+    payee: PubKeyBin,
+    amount: u64,
+    memo: u64,
+    nonce: u64,
+    fee_config: &TxnFeeConfig,
+) -> Result<BlockchainTxnTokenBurnV1> {
+    let mut txn = BlockchainTxnTokenBurnV1 {
+        fee: 0,
+        payee: payee.into(),
+        amount,
+        payer: payer.into(),
+        memo,
+        nonce,
+        signature: Vec::new(),
+    };
+    txn.fee = txn.txn_fee(fee_config)?;
+    Ok(txn)
+}
+
+fn print_txn(
+    txn: &BlockchainTxnTokenBurnV1,
+    envelope: &BlockchainTxn,
+    conversion: &Option<Conversion>,
+    cost: &FeeCost,
+    status: &Option<PendingTxnStatus>,
+    format: OutputFormat,
+) -> Result {
+    match format {
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.add_row(row!["Key", "Value"]);
+            table.add_row(row![
+                "Payee",
+                PubKeyBin::from_vec(&txn.payee).to_b58().unwrap()
+            ]);
+            table.add_row(row!["Memo", txn.memo.to_b64()?]);
+            table.add_row(row!["Memo (Decimal)", txn.memo]);
+            table.add_row(row!["Memo (Hex)", Memo(txn.memo).to_hex()]);
+            table.add_row(row![
+                "Memo (ASCII)",
+                Memo(txn.memo).to_ascii().unwrap_or_default()
+            ]);
+            table.add_row(row!["Amount", Hnt::from_bones(txn.amount)]);
+            if let Some(conversion) = conversion {
+                table.add_row(row!["DC", conversion.dc]);
+                table.add_row(row!["Value (USD)", dc_to_usd(conversion.dc)]);
+                table.add_row(row![
+                    "Oracle Price (USD)",
+                    oracle_price_usd(conversion.oracle_price)
+                ]);
+            }
+            table.add_row(row!["Fee (DC)", cost.dc]);
+            table.add_row(row!["Fee (HNT)", cost.hnt]);
+            table.add_row(row!["Implicit Burn", cost.implicit_burn]);
+            table.add_row(row!["Nonce", txn.nonce]);
+            table.add_row(row!["Hash", status_str(envelope, status)?]);
+            print_table(&table)?;
+            print_footer(status)
+        }
+        OutputFormat::Json => {
+            let table = json!({
+                "payee": PubKeyBin::from_vec(&txn.payee).to_b58().unwrap(),
+                "amount": Hnt::from_bones(txn.amount),
+                "memo": txn.memo.to_b64()?,
+                "memo_decimal": txn.memo,
+                "memo_hex": Memo(txn.memo).to_hex(),
+                "memo_ascii": Memo(txn.memo).to_ascii(),
+                "dc": conversion.map(|c| c.dc),
+                "usd": conversion.map(|c| dc_to_usd(c.dc)),
+                "oracle_price": conversion.map(|c| oracle_price_usd(c.oracle_price)),
+                "fee": cost.dc,
+                "fee_hnt": cost.hnt,
+                "implicit_burn": cost.implicit_burn,
+                "nonce": txn.nonce,
+                "hash": status_json(envelope, status)?,
+                "txn": envelope.to_b64()?
+            });
+            print_json(&table)
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use crate::{
+    cmd::api_url,
+    keypair::PubKeyBin,
+    result::Result,
+    traits::{ToJson, TxnValidate},
+};
+use helium_api::{BlockchainTxn, Hnt, Hst, Txn};
+use prettytable::{format, Table};
+use std::{env, str::FromStr};
+
+/// The amount of HNT above which a transaction always needs to be
+/// confirmed interactively
+const CONFIRM_HNT_VAR: &str = "HELIUM_WALLET_CONFIRM_HNT";
+/// The fee in DC above which a transaction always needs to be
+/// confirmed interactively
+const CONFIRM_DC_VAR: &str = "HELIUM_WALLET_CONFIRM_DC";
+
+/// A summary of a transaction to be confirmed
+struct Summary {
+    txn_type: String,
+    payees: Vec<(PubKeyBin, String)>,
+    total: String,
+    /// The total amount of HNT spent in bones
+    hnt: u64,
+    fee: u64,
+    nonce: Option<u64>,
+    /// Additional rows describing what the transaction is for
+    details: Vec<(&'static str, String)>,
+}
+
+impl Summary {
+    fn new(envelope: &BlockchainTxn, details: &[(&'static str, String)]) -> Result<Self> {
+        let mut summary = Self {
+            txn_type: envelope.to_json()?["type"]
+                .as_str()
+                .unwrap_or("unknown")
+                .to_string(),
+            payees: vec![],
+            total: Hnt::from_bones(0).to_string(),
+            hnt: 0,
+            fee: envelope.total_fee(),
+            nonce: None,
+            details: details.to_vec(),
+        };
+        match &envelope.txn {
+            Some(Txn::Payment(t)) => {
+                summary.add_hnt(&t.payee, t.amount);
+                summary.nonce = Some(t.nonce);
+            }
+            Some(Txn::PaymentV2(t)) => {
+                for payment in &t.payments {
+                    summary.add_hnt(&payment.payee, payment.amount);
+                }
+                summary.nonce = Some(t.nonce);
+            }
+            Some(Txn::CreateHtlc(t)) => {
+                summary.add_hnt(&t.payee, t.amount);
+                summary.nonce = Some(t.nonce);
+            }
+            Some(Txn::TokenBurn(t)) => {
+                summary.add_hnt(&t.payee, t.amount);
+                summary.nonce = Some(t.nonce);
+            }
+            Some(Txn::TransferHotspot(t)) => {
+                summary.add_hnt(&t.seller, t.amount_to_seller);
+                summary.nonce = Some(t.buyer_nonce);
+            }
+            Some(Txn::SecurityExchange(t)) => {
+                let amount = Hst::from_bones(t.amount).to_string();
+                summary
+                    .payees
+                    .push((PubKeyBin::from_vec(&t.payee), amount.clone()));
+                summary.total = amount;
+                summary.nonce = Some(t.nonce);
+            }
+            Some(Txn::AssertLocation(t)) => summary.nonce = Some(t.nonce),
+            _ => (),
+        }
+        Ok(summary)
+    }
+
+    fn add_hnt(&mut self, payee: &[u8], amount: u64) {
+        self.payees.push((
+            PubKeyBin::from_vec(payee),
+            Hnt::from_bones(amount).to_string(),
+        ));
+        // A total too large to represent still exceeds every threshold
+        self.hnt = self.hnt.saturating_add(amount);
+        self.total = Hnt::from_bones(self.hnt).to_string();
+    }
+
+    fn print(&self) -> Result {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+        table.set_titles(row!["Key", "Value"]);
+        table.add_row(row!["Type", self.txn_type]);
+        for (key, value) in &self.details {
+            table.add_row(row![key, value]);
+        }
+        for (payee, amount) in &self.payees {
+            table.add_row(row!["Payee", format!("{} = {}", payee, amount)]);
+        }
+        table.add_row(row!["Total", self.total]);
+        table.add_row(row!["Fee (DC)", self.fee]);
+        if let Some(nonce) = self.nonce {
+            table.add_row(row!["Nonce", nonce]);
+        }
+        table.add_row(row!["Network", api_url()]);
+        eprint!("{}", table);
+        Ok(())
+    }
+}
+
+/// Returns the reason a transaction always needs interactive
+/// confirmation, if any.
+fn threshold_exceeded(summary: &Summary) -> Result<Option<String>> {
+    if let Ok(hnt) = env::var(CONFIRM_HNT_VAR) {
+        let threshold = Hnt::from_str(&hnt)
+            .map_err(|_| format!("Invalid {} value \"{}\"", CONFIRM_HNT_VAR, hnt))?;
+        if summary.hnt > threshold.to_bones() {
+            return Ok(Some(format!(
+                "Total of {} exceeds the confirmation threshold of {}",
+                Hnt::from_bones(summary.hnt),
+                threshold
+            )));
+        }
+    }
+    if let Ok(dc) = env::var(CONFIRM_DC_VAR) {
+        let threshold = u64::from_str(&dc)
+            .map_err(|_| format!("Invalid {} value \"{}\"", CONFIRM_DC_VAR, dc))?;
+        if summary.fee > threshold {
+            return Ok(Some(format!(
+                "Fee of {} DC exceeds the confirmation threshold of {} DC",
+                summary.fee, threshold
+            )));
+        }
+    }
+    Ok(None)
+}
+
+/// Ask for a typed confirmation before the given transaction is
+/// committed. Confirmation is skipped when stdin is not a terminal or
+/// when `yes` is given, unless the transaction exceeds one of the
+/// confirmation thresholds. The given details are included in the
+/// summary that is shown.
+pub fn confirm_txn(
+    envelope: &BlockchainTxn,
+    yes: bool,
+    details: &[(&'static str, String)],
+) -> Result {
+    let summary = Summary::new(envelope, details)?;
+    let exceeded = threshold_exceeded(&summary)?;
+    let interactive = atty::is(atty::Stream::Stdin);
+    match (&exceeded, interactive) {
+        (None, false) => return Ok(()),
+        (None, true) if yes => return Ok(()),
+        (Some(reason), false) => {
+            return Err(format!("{} and stdin is not a terminal to confirm it", reason).into())
+        }
+        _ => (),
+    }
+    summary.print()?;
+    if let Some(reason) = exceeded {
+        eprintln!("{}", reason);
+    }
+    let answer = dialoguer::Input::<String>::new()
+        .with_prompt("Type \"yes\" to commit the transaction")
+        .allow_empty(true)
+        .interact()?;
+    if answer.trim() == "yes" {
+        Ok(())
+    } else {
+        Err("Transaction not committed".into())
+    }
+}
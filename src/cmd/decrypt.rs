@@ -0,0 +1,47 @@
+use crate::{
+    cmd::{get_password, load_wallet, read_input, write_output, Opts},
+    result::Result,
+    sealed::SealedMessage,
+};
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+/// Decrypt a message that was encrypted to the address of this
+/// wallet.
+pub struct Cmd {
+    /// File with the armored message to decrypt. If no file is given
+    /// stdin is read. Note that the stdin feature only works if the
+    /// wallet password is set in the HELIUM_WALLET_PASSWORD
+    /// environment variable
+    #[structopt(name = "FILE")]
+    input: Option<PathBuf>,
+
+    /// File to write the decrypted message to. If no file is given
+    /// the message is written to stdout.
+    #[structopt(short, long)]
+    output: Option<PathBuf>,
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        let armor = String::from_utf8(read_input(&self.input)?)?;
+        let sealed = SealedMessage::from_armor(&armor)?;
+
+        let password = get_password(false)?;
+        let wallet = load_wallet(opts.files)?;
+        let keypair = wallet.decrypt(password.as_bytes())?;
+
+        let message = sealed.open(&keypair)?;
+        match &self.output {
+            Some(output) => write_output(output, &message),
+            None => {
+                io::stdout().write_all(&message)?;
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use crate::{
+    cmd::{print_json, read_input, write_output, Opts, OutputFormat},
+    keypair::PubKeyBin,
+    result::Result,
+    sealed::{unique_recipients, SealedMessage},
+};
+use serde_json::json;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+/// Encrypt a message to one or more Helium addresses. Only the holders
+/// of the wallets for the given addresses will be able to decrypt the
+/// message.
+pub struct Cmd {
+    /// Address of a recipient. Use this option multiple times for
+    /// multiple recipients.
+    #[structopt(long = "to", short = "t", number_of_values(1), required = true)]
+    recipients: Vec<PubKeyBin>,
+
+    /// File to encrypt. If no file is given stdin is read.
+    #[structopt(name = "FILE")]
+    input: Option<PathBuf>,
+
+    /// File to write the armored message to. If no file is given
+    /// the message is printed.
+    #[structopt(short, long)]
+    output: Option<PathBuf>,
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        let message = read_input(&self.input)?;
+        let sealed = SealedMessage::seal(&message, &self.recipients)?;
+        let armor = sealed.to_armor()?;
+        if let Some(output) = &self.output {
+            return write_output(output, armor.as_bytes());
+        }
+        print_sealed(&unique_recipients(&self.recipients), &armor, opts.format)
+    }
+}
+
+fn print_sealed(recipients: &[PubKeyBin], armor: &str, format: OutputFormat) -> Result {
+    match format {
+        OutputFormat::Table => {
+            println!("{}", armor);
+            Ok(())
+        }
+        OutputFormat::Json => {
+            let table = json!({
+                "recipients": recipients
+                    .iter()
+                    .map(|r| r.to_string())
+                    .collect::<Vec<String>>(),
+                "message": armor,
+            });
+            print_json(&table)
+        }
+    }
+}
@@ -0,0 +1,371 @@
+use crate::{
+    amount::Amount,
+    api,
+    cmd::{
+        api_url, burn, cached_oracle_price, get_nonce, get_txn_fees, htlc, load_wallet, pay,
+        print_json, print_table, read_txn, securities, Opts, OutputFormat,
+    },
+    keypair::Keypair,
+    nonce::NonceKind,
+    result::Result,
+    traits::{txn_fee::dc_to_bones, ToJson, TxnEnvelope, TxnFee, TxnFeeConfig, TxnStakingFee},
+};
+use helium_api::{
+    BlockchainTxn, BlockchainTxnAddGatewayV1, BlockchainTxnAssertLocationV1, BlockchainTxnOuiV1,
+    BlockchainTxnTransferHotspotV1, Client, Hnt,
+};
+use rust_decimal::Decimal;
+use serde_json::json;
+use structopt::StructOpt;
+
+/// A resolution 12 h3 index, used as the location for assert location
+/// estimates. All indexes of the same resolution encode to the same
+/// size.
+const SAMPLE_LOCATION: &str = "8c283082a1b2bff";
+
+#[derive(Debug, StructOpt)]
+/// Display the current transaction fee schedule or estimate the fee of
+/// a transaction. Fees are in Data Credits (DC) and are converted to
+/// HNT and USD using the current oracle price.
+pub enum Cmd {
+    Schedule(Schedule),
+    Estimate(Estimate),
+}
+
+#[derive(Debug, StructOpt)]
+/// Display the current transaction fee configuration and staking fees
+pub struct Schedule {}
+
+#[derive(Debug, StructOpt)]
+/// Estimate the fee for a transaction. Wallet transactions are built
+/// from the public part of the wallet, so no password is required. The
+/// --commit option of the given transaction is ignored.
+pub enum Estimate {
+    Pay(pay::Cmd),
+    Burn(burn::Cmd),
+    HtlcCreate(htlc::Create),
+    HtlcRedeem(htlc::Redeem),
+    SecuritiesTransfer(securities::Transfer),
+    /// Estimate the fee for adding a hotspot, including the staking fee
+    AddGateway(Hotspot),
+    /// Estimate the fee for asserting a hotspot location, including
+    /// the staking fee
+    AssertLocation(Hotspot),
+    TransferHotspot(TransferHotspot),
+    Oui(Oui),
+    Txn(Txn),
+}
+
+#[derive(Debug, StructOpt)]
+pub struct Hotspot {
+    /// Estimate for a transaction with a payer other than the owner
+    #[structopt(long)]
+    payer: bool,
+}
+
+#[derive(Debug, StructOpt)]
+/// Estimate the fee for a hotspot transfer
+pub struct TransferHotspot {
+    /// Price to be paid by recipient of transfer, in HNT unless a unit
+    /// is given
+    #[structopt(long)]
+    price: Option<Amount>,
+}
+
+#[derive(Debug, StructOpt)]
+/// Estimate the fee for an OUI transaction
+pub struct Oui {
+    /// Requested subnet size. Must be a value between 8 and 65,536
+    /// and a power of two.
+    #[structopt(long, default_value = "8")]
+    subnet_size: u32,
+
+    /// The number of router addresses
+    #[structopt(long, default_value = "1")]
+    addresses: usize,
+
+    /// Estimate for a transaction with a payer other than the owner
+    #[structopt(long)]
+    payer: bool,
+}
+
+#[derive(Debug, StructOpt)]
+/// Estimate the fee for an existing transaction
+pub struct Txn {
+    /// Base64 encoded transaction, a file containing the transaction
+    /// or "-" to read the transaction from stdin.
+    #[structopt(name = "TRANSACTION")]
+    txn: Option<String>,
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        match self {
+            Cmd::Schedule(cmd) => cmd.run(opts),
+            Cmd::Estimate(cmd) => cmd.run(opts),
+        }
+    }
+}
+
+impl Schedule {
+    pub fn run(&self, opts: Opts) -> Result {
+        let client = Client::new_with_base_url(api_url());
+        let fee_config = get_txn_fees(&client, &opts.vars_file)?;
+        let oracle_price = api::Client::new_with_base_url(api_url()).get_oracle_price()?;
+        print_schedule(&fee_config, oracle_price, opts.format)
+    }
+}
+
+impl Estimate {
+    pub fn run(&self, opts: Opts) -> Result {
+        let client = Client::new_with_base_url(api_url());
+        let fee_config = get_txn_fees(&client, &opts.vars_file)?;
+        let envelope = match self {
+            Estimate::Txn(cmd) => read_txn(&cmd.txn)?,
+            Estimate::AddGateway(cmd) => add_gateway_txn(cmd.payer, &fee_config)?.in_envelope(),
+            Estimate::AssertLocation(cmd) => {
+                assert_location_txn(cmd.payer, &fee_config)?.in_envelope()
+            }
+            Estimate::TransferHotspot(cmd) => cmd.mk_txn()?.in_envelope(),
+            Estimate::Oui(cmd) => cmd.mk_txn(&fee_config)?.in_envelope(),
+            _ => self.wallet_txn(&client, opts.files.clone(), &fee_config)?,
+        };
+        let fee = envelope.txn_fee(&fee_config)?;
+        let staking_fee = envelope.txn_staking_fee(&fee_config)?;
+        let oracle_price = api::Client::new_with_base_url(api_url()).get_oracle_price()?;
+        print_estimate(&envelope, fee, staking_fee, oracle_price, opts.format)
+    }
+
+    /// Build the transaction for one of the wallet commands
+    fn wallet_txn(
+        &self,
+        client: &Client,
+        files: Vec<std::path::PathBuf>,
+        fee_config: &TxnFeeConfig,
+    ) -> Result<BlockchainTxn> {
+        let address = load_wallet(files)?.pubkey_bin;
+        let hnt_nonce = |nonce| get_nonce(client, &address, NonceKind::Hnt, nonce, false);
+        let envelope = match self {
+            Estimate::Pay(cmd) => {
+                let request = cmd.payment_request()?;
+                let nonce = hnt_nonce(cmd.nonce)?;
+                cmd.mk_txn(client, address, nonce.nonce, fee_config, request.as_ref())?
+                    .in_envelope()
+            }
+            Estimate::Burn(cmd) => {
+                let nonce = hnt_nonce(cmd.nonce)?;
+                cmd.mk_txn(address, nonce.nonce, fee_config)?.in_envelope()
+            }
+            Estimate::HtlcCreate(cmd) => {
+                let nonce = hnt_nonce(cmd.nonce)?;
+                cmd.mk_txn(address, nonce.nonce, fee_config)?.in_envelope()
+            }
+            Estimate::HtlcRedeem(cmd) => cmd.mk_txn(address, fee_config)?.in_envelope(),
+            Estimate::SecuritiesTransfer(cmd) => {
+                let nonce = get_nonce(client, &address, NonceKind::Security, cmd.nonce, false)?;
+                cmd.mk_txn(address, nonce.nonce, fee_config)?.in_envelope()
+            }
+            _ => return Err("Not a wallet transaction".into()),
+        };
+        Ok(envelope)
+    }
+}
+
+/// A random address to stand in for the parties of a transaction that
+/// is only used for fee estimation.
+fn sample_address() -> Vec<u8> {
+    Keypair::gen_keypair().pubkey_bin().into()
+}
+
+fn sample_payer(payer: bool) -> Vec<u8> {
+    if payer {
+        sample_address()
+    } else {
+        vec![]
+    }
+}
+
+// The staking fee is part of the encoded transaction and so is set
+// before the transaction fee is calculated.
+
+fn add_gateway_txn(payer: bool, fee_config: &TxnFeeConfig) -> Result<BlockchainTxnAddGatewayV1> {
+    let mut txn = BlockchainTxnAddGatewayV1 {
+        owner: sample_address(),
+        gateway: sample_address(),
+        payer: sample_payer(payer),
+        staking_fee: 0,
+        fee: 0,
+        owner_signature: vec![],
+        gateway_signature: vec![],
+        payer_signature: vec![],
+    };
+    txn.staking_fee = txn.txn_staking_fee(fee_config)?;
+    Ok(txn)
+}
+
+fn assert_location_txn(
+    payer: bool,
+    fee_config: &TxnFeeConfig,
+) -> Result<BlockchainTxnAssertLocationV1> {
+    let mut txn = BlockchainTxnAssertLocationV1 {
+        gateway: sample_address(),
+        owner: sample_address(),
+        payer: sample_payer(payer),
+        gateway_signature: vec![],
+        owner_signature: vec![],
+        payer_signature: vec![],
+        location: SAMPLE_LOCATION.to_string(),
+        nonce: 1,
+        staking_fee: 0,
+        fee: 0,
+    };
+    txn.staking_fee = txn.txn_staking_fee(fee_config)?;
+    Ok(txn)
+}
+
+impl TransferHotspot {
+    fn mk_txn(&self) -> Result<BlockchainTxnTransferHotspotV1> {
+        let amount_to_seller = match self.price {
+            Some(price) => price.to_bones(|| cached_oracle_price(&mut None))?,
+            None => 0,
+        };
+        Ok(BlockchainTxnTransferHotspotV1 {
+            gateway: sample_address(),
+            seller: sample_address(),
+            buyer: sample_address(),
+            seller_signature: vec![],
+            buyer_signature: vec![],
+            buyer_nonce: 1,
+            amount_to_seller,
+            fee: 0,
+        })
+    }
+}
+
+impl Oui {
+    fn mk_txn(&self, fee_config: &TxnFeeConfig) -> Result<BlockchainTxnOuiV1> {
+        let mut txn = BlockchainTxnOuiV1 {
+            owner: sample_address(),
+            payer: sample_payer(self.payer),
+            addresses: (0..self.addresses).map(|_| sample_address()).collect(),
+            filter: vec![0; 32],
+            requested_subnet_size: self.subnet_size,
+            oui: 1,
+            staking_fee: 0,
+            fee: 0,
+            owner_signature: vec![],
+            payer_signature: vec![],
+        };
+        txn.staking_fee = txn.txn_staking_fee(fee_config)?;
+        Ok(txn)
+    }
+}
+
+/// The USD value of the given DC amount
+pub(crate) fn dc_to_usd(dc: u64) -> Decimal {
+    Decimal::new(dc as i64, 5)
+}
+
+fn dc_to_hnt(dc: u64, oracle_price: u64) -> Result<Hnt> {
+    Ok(Hnt::from_bones(dc_to_bones(dc, oracle_price)?))
+}
+
+pub(crate) fn oracle_price_usd(oracle_price: u64) -> Decimal {
+    Decimal::new(oracle_price as i64, 8)
+}
+
+fn print_schedule(fee_config: &TxnFeeConfig, oracle_price: u64, format: OutputFormat) -> Result {
+    let config = serde_json::to_value(fee_config)?;
+    let staking_fees: Vec<(&String, u64)> = config
+        .as_object()
+        .ok_or("Invalid fee configuration")?
+        .iter()
+        .filter(|(key, _)| key.starts_with("staking_fee"))
+        .filter_map(|(key, value)| value.as_u64().map(|fee| (key, fee)))
+        .collect();
+    match format {
+        OutputFormat::Table => {
+            ptable!(
+                ["Key", "Value"],
+                ["Transaction Fees", config["txn_fees"]],
+                ["Fee Multiplier", config["txn_fee_multiplier"]],
+                ["DC Payload Size", fee_config.dc_payload_size()],
+                ["Oracle Price", oracle_price_usd(oracle_price)]
+            );
+            let mut table = prettytable::Table::new();
+            table.set_format(*prettytable::format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+            table.set_titles(row!["Staking Fee", "DC", "HNT", "USD"]);
+            for (key, fee) in staking_fees {
+                table.add_row(row![
+                    key,
+                    fee,
+                    dc_to_hnt(fee, oracle_price)?,
+                    dc_to_usd(fee)
+                ]);
+            }
+            print_table(&table)
+        }
+        OutputFormat::Json => {
+            let mut json_fees = serde_json::Map::new();
+            for (key, fee) in staking_fees {
+                json_fees.insert(
+                    key.to_string(),
+                    json!({
+                        "dc": fee,
+                        "hnt": dc_to_hnt(fee, oracle_price)?,
+                        "usd": dc_to_usd(fee),
+                    }),
+                );
+            }
+            let table = json!({
+                "txn_fees": config["txn_fees"],
+                "txn_fee_multiplier": config["txn_fee_multiplier"],
+                "dc_payload_size": fee_config.dc_payload_size(),
+                "oracle_price": oracle_price_usd(oracle_price),
+                "staking_fees": json_fees,
+            });
+            print_json(&table)
+        }
+    }
+}
+
+fn print_estimate(
+    envelope: &BlockchainTxn,
+    fee: u64,
+    staking_fee: u64,
+    oracle_price: u64,
+    format: OutputFormat,
+) -> Result {
+    let txn_type = envelope.to_json()?["type"].clone();
+    let total = fee + staking_fee;
+    let total_hnt = dc_to_hnt(total, oracle_price)?;
+    match format {
+        OutputFormat::Table => {
+            ptable!(
+                ["Key", "Value"],
+                ["Type", txn_type.as_str().unwrap_or("unknown")],
+                ["Fee (DC)", fee],
+                ["Staking Fee (DC)", staking_fee],
+                ["Total (DC)", total],
+                ["Total (HNT)", total_hnt],
+                ["Total (USD)", dc_to_usd(total)],
+                ["Oracle Price", oracle_price_usd(oracle_price)]
+            );
+            Ok(())
+        }
+        OutputFormat::Json => {
+            let table = json!({
+                "type": txn_type,
+                "fee": fee,
+                "staking_fee": staking_fee,
+                "total": {
+                    "dc": total,
+                    "hnt": total_hnt,
+                    "usd": dc_to_usd(total),
+                },
+                "oracle_price": oracle_price_usd(oracle_price),
+            });
+            print_json(&table)
+        }
+    }
+}
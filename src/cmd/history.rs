@@ -0,0 +1,85 @@
+use crate::{
+    cmd::{data_dir, load_wallet, print_json, print_table, Opts, OutputFormat},
+    journal::{Journal, JournalEntry},
+    result::Result,
+};
+use prettytable::{format, Table};
+use std::{fs, io, path::PathBuf};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+/// Display the transaction history of a wallet
+pub enum Cmd {
+    Local(Local),
+}
+
+#[derive(Debug, StructOpt)]
+/// Display the transactions built, signed and submitted by this
+/// wallet as recorded in its local journal. Every transaction is
+/// shown with its latest status unless --all is given.
+pub struct Local {
+    /// Show every journal entry instead of only the latest status of
+    /// each transaction
+    #[structopt(long)]
+    all: bool,
+
+    /// Export the history as CSV to the given file, or to stdout if
+    /// "-" is given
+    #[structopt(long, value_name = "file")]
+    csv: Option<PathBuf>,
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        match self {
+            Cmd::Local(cmd) => cmd.run(opts),
+        }
+    }
+}
+
+impl Local {
+    pub fn run(&self, opts: Opts) -> Result {
+        let wallet = load_wallet(opts.files)?;
+        let journal = Journal::open(&data_dir()?, &wallet.pubkey_bin)?;
+        let entries = if self.all {
+            journal.entries()?
+        } else {
+            journal.latest()?
+        };
+        match &self.csv {
+            Some(path) if path.to_str() == Some("-") => write_csv(io::stdout(), &entries),
+            Some(path) => write_csv(fs::File::create(path)?, &entries),
+            None => print_entries(&entries, opts.format),
+        }
+    }
+}
+
+fn write_csv<W: io::Write>(output: W, entries: &[JournalEntry]) -> Result {
+    let mut writer = csv::Writer::from_writer(output);
+    for entry in entries {
+        writer.serialize(entry)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn print_entries(entries: &[JournalEntry], format: OutputFormat) -> Result {
+    match format {
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+            table.set_titles(row!["Time", "Hash", "Type", "Nonce", "Status"]);
+            for entry in entries {
+                table.add_row(row![
+                    entry.time,
+                    entry.hash,
+                    entry.txn_type,
+                    entry.nonce.map_or_else(String::new, |n| n.to_string()),
+                    entry.status
+                ]);
+            }
+            print_table(&table)
+        }
+        OutputFormat::Json => print_json(entries),
+    }
+}
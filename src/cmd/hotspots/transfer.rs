@@ -1,11 +1,13 @@
 use crate::{
+    amount::Amount,
     cmd::{
-        api_url, get_password, get_txn_fees, load_wallet, print_json, status_json, Opts,
-        OutputFormat,
+        api_url, cached_oracle_price, get_password, get_txn_fees, load_wallet, print_footer,
+        print_json, sign_txn, status_json, status_str, CommitOpts, FeeCost, Opts, OutputFormat,
+        QrOpts,
     },
     keypair::PubKeyBin,
     result::Result,
-    traits::{Sign, TxnEnvelope, TxnFee, B58, B64},
+    traits::{TxnEnvelope, TxnFee, TxnVerify, B58, B64},
 };
 use helium_api::{
     BlockchainTxn, BlockchainTxnTransferHotspotV1, Client, Hnt, PendingTxnStatus, Txn,
@@ -29,8 +31,12 @@ pub struct Sell {
     gateway: String,
     /// The recipient of the gateway transfer
     buyer: String,
-    /// Price in HNT to be paid by recipient of transfer
-    price: Option<Hnt>,
+    /// Price to be paid by recipient of transfer, in HNT unless a unit
+    /// of bones, dc or usd is given
+    price: Option<Amount>,
+
+    #[structopt(flatten)]
+    qr: QrOpts,
 }
 
 #[derive(Debug, StructOpt)]
@@ -41,9 +47,9 @@ pub struct Buy {
     /// HELIUM_WALLET_PASSWORD environment variable
     #[structopt(name = "TRANSACTION")]
     txn: Option<String>,
-    #[structopt(long)]
-    /// Commit to sign and submit the transaction
-    commit: bool,
+
+    #[structopt(flatten)]
+    commit: CommitOpts,
 }
 
 impl Buy {
@@ -78,19 +84,25 @@ impl Transfer {
                     buyer: buyer.to_vec(),
                     seller_signature: vec![],
                     buyer_signature: vec![],
-                    amount_to_seller: sell.price.unwrap_or_else(|| Hnt::from_bones(0)).to_bones(),
+                    amount_to_seller: match sell.price {
+                        Some(price) => price.to_bones(|| cached_oracle_price(&mut None))?,
+                        None => 0,
+                    },
                     buyer_nonce: buyer_account.speculative_nonce + 1,
                 };
-                txn.fee = txn.txn_fee(&get_txn_fees(&client)?)?;
+                txn.fee = txn.txn_fee(&get_txn_fees(&client, &opts.vars_file)?)?;
                 let password = get_password(false)?;
                 let keypair = wallet.decrypt(password.as_bytes())?;
-                txn.seller_signature = txn.sign(&keypair)?;
-                println!("{}", txn.in_envelope().to_b64()?);
-                Ok(())
+                txn.seller_signature = sign_txn(&txn, &keypair)?;
+                let envelope = txn.in_envelope().to_b64()?;
+                println!("{}", envelope);
+                sell.qr.write(&envelope)
             }
 
             Self::Buy(buy) => {
                 let mut envelope = BlockchainTxn::from_b64(&buy.read_txn()?)?;
+                // Refuse to co-sign a transfer the seller did not sign
+                envelope.verify_signature("seller")?;
 
                 match &mut envelope.txn {
                     Some(Txn::TransferHotspot(t)) => {
@@ -110,13 +122,12 @@ impl Transfer {
 
                         let password = get_password(false)?;
                         let keypair = wallet.decrypt(password.as_bytes())?;
-                        t.buyer_signature = t.sign(&keypair)?;
-                        let status = if buy.commit {
-                            Some(client.submit_txn(&envelope)?)
-                        } else {
-                            None
-                        };
-                        print_txn(&envelope, &status, opts.format)
+                        t.buyer_signature = sign_txn(t, &keypair)?;
+                        let txn = t.clone();
+                        let cost = buy.commit.validate(&client, &txn)?;
+                        let status = buy.commit.submit(&client, &envelope, None)?;
+                        print_txn(&txn, &envelope, &cost, &status, opts.format)?;
+                        buy.commit.maybe_wait(&status, opts.format)
                     }
                     _ => Err("Unsupported transaction for transfer_hotspot".into()),
                 }
@@ -126,17 +137,36 @@ impl Transfer {
 }
 
 fn print_txn(
+    txn: &BlockchainTxnTransferHotspotV1,
     envelope: &BlockchainTxn,
+    cost: &FeeCost,
     status: &Option<PendingTxnStatus>,
     format: OutputFormat,
 ) -> Result {
     let encoded = envelope.to_b64()?;
     match format {
-        OutputFormat::Table => Err("Table format not supported for transaction output".into()),
+        OutputFormat::Table => {
+            ptable!(
+                ["Key", "Value"],
+                ["Gateway", PubKeyBin::from_vec(&txn.gateway).to_b58()?],
+                ["Seller", PubKeyBin::from_vec(&txn.seller).to_b58()?],
+                ["Buyer", PubKeyBin::from_vec(&txn.buyer).to_b58()?],
+                ["Amount (HNT)", Hnt::from_bones(txn.amount_to_seller)],
+                ["Buyer Nonce", txn.buyer_nonce],
+                ["Fee (DC)", cost.dc],
+                ["Fee (HNT)", cost.hnt],
+                ["Implicit Burn", cost.implicit_burn],
+                ["Hash", status_str(envelope, status)?]
+            );
+            print_footer(status)
+        }
         OutputFormat::Json => {
             let table = json!({
                 "txn": encoded,
-                "hash": status_json(status)
+                "fee": cost.dc,
+                "fee_hnt": cost.hnt,
+                "implicit_burn": cost.implicit_burn,
+                "hash": status_json(envelope, status)?
             });
             print_json(&table)
         }
@@ -1,15 +1,17 @@
 use crate::{
+    amount::Amount,
     cmd::{
-        api_url, get_password, get_txn_fees, load_wallet, print_footer, print_json, status_json,
-        status_str, Opts, OutputFormat,
+        api_url, cached_oracle_price, get_nonce, get_password, get_txn_fees, load_wallet,
+        print_footer, print_json, sign_txn, status_json, status_str, CommitOpts, FeeCost, Opts,
+        OutputFormat,
     },
     keypair::{Keypair, PubKeyBin},
+    nonce::NonceKind,
     result::Result,
-    traits::{Sign, TxnEnvelope, TxnFee, B58, B64},
+    traits::{TxnEnvelope, TxnFee, TxnFeeConfig, B58, B64},
 };
 use helium_api::{
-    BlockchainTxn, BlockchainTxnCreateHtlcV1, BlockchainTxnRedeemHtlcV1, Client, Hnt,
-    PendingTxnStatus,
+    BlockchainTxn, BlockchainTxnCreateHtlcV1, BlockchainTxnRedeemHtlcV1, Client, PendingTxnStatus,
 };
 use serde_json::json;
 use structopt::StructOpt;
@@ -28,9 +30,9 @@ pub struct Create {
     /// The address of the intended payee for this HTLC
     payee: String,
 
-    /// Number of hnt to send
+    /// Amount to send, in HNT unless a unit of bones, dc or usd is given
     #[structopt(long)]
-    hnt: Hnt,
+    hnt: Amount,
 
     /// A hex encoded SHA256 digest of a secret value (called a preimage) that locks this contract
     #[structopt(long = "hashlock")]
@@ -40,9 +42,12 @@ pub struct Create {
     #[structopt(long = "timelock")]
     timelock: u64,
 
-    /// Commit the payment to the API
+    /// Manually set the nonce to use for the transaction
     #[structopt(long)]
-    commit: bool,
+    pub(crate) nonce: Option<u64>,
+
+    #[structopt(flatten)]
+    commit: CommitOpts,
 }
 
 #[derive(Debug, StructOpt)]
@@ -59,9 +64,8 @@ pub struct Redeem {
     #[structopt(long)]
     hash: bool,
 
-    /// Commit the payment to the API
-    #[structopt(long)]
-    commit: bool,
+    #[structopt(flatten)]
+    commit: CommitOpts,
 }
 
 impl Cmd {
@@ -80,37 +84,56 @@ impl Create {
         let client = Client::new_with_base_url(api_url());
 
         let keypair = wallet.decrypt(password.as_bytes())?;
-        let account = client.get_account(&keypair.public.to_b58()?)?;
-        let address = Keypair::gen_keypair().pubkey_bin();
+        let fee_config = get_txn_fees(&client, &opts.vars_file)?;
+        let nonce = get_nonce(
+            &client,
+            &keypair.pubkey_bin(),
+            NonceKind::Hnt,
+            self.nonce,
+            self.commit.commit,
+        )?;
+
+        let mut txn = self.mk_txn(keypair.pubkey_bin(), nonce.nonce, &fee_config)?;
+        txn.signature = sign_txn(&txn, &keypair)?;
+        let envelope = txn.in_envelope();
+        let cost = self.commit.validate(&client, &txn)?;
+
+        let status = self.commit.submit(&client, &envelope, Some(nonce))?;
+
+        print_create_txn(&txn, &envelope, &cost, &status, opts.format)?;
+
+        self.commit.maybe_wait(&status, opts.format)
+    }
 
+    /// Construct the unsigned HTLC creation transaction for the given
+    /// payer. A new random address is generated for the contract.
+    pub(crate) fn mk_txn(
+        &self,
+        payer: PubKeyBin,
+        nonce: u64,
+        fee_config: &TxnFeeConfig,
+    ) -> Result<BlockchainTxnCreateHtlcV1> {
+        let address = Keypair::gen_keypair().pubkey_bin();
         let mut txn = BlockchainTxnCreateHtlcV1 {
-            amount: self.hnt.to_bones(),
+            amount: self.hnt.to_bones(|| cached_oracle_price(&mut None))?,
             fee: 0,
             payee: PubKeyBin::from_b58(&self.payee)?.into(),
-            payer: keypair.pubkey_bin().into(),
+            payer: payer.into(),
             address: address.into(),
-            hashlock: hex::decode(self.hashlock.clone()).unwrap(),
+            hashlock: hex::decode(self.hashlock.clone())?,
             timelock: self.timelock,
-            nonce: account.speculative_nonce + 1,
+            nonce,
             signature: Vec::new(),
         };
-        txn.fee = txn.txn_fee(&get_txn_fees(&client)?)?;
-        txn.signature = txn.sign(&keypair)?;
-        let envelope = txn.in_envelope();
-
-        let status = if self.commit {
-            Some(client.submit_txn(&envelope)?)
-        } else {
-            None
-        };
-
-        print_create_txn(&txn, &envelope, &status, opts.format)
+        txn.fee = txn.txn_fee(fee_config)?;
+        Ok(txn)
     }
 }
 
 fn print_create_txn(
     txn: &BlockchainTxnCreateHtlcV1,
     envelope: &BlockchainTxn,
+    cost: &FeeCost,
     status: &Option<PendingTxnStatus>,
     format: OutputFormat,
 ) -> Result {
@@ -123,8 +146,11 @@ fn print_create_txn(
                 ["Amount", txn.amount],
                 ["Hashlock", hex::encode(&txn.hashlock)],
                 ["Timelock", txn.timelock],
+                ["Fee (DC)", cost.dc],
+                ["Fee (HNT)", cost.hnt],
+                ["Implicit Burn", cost.implicit_burn],
                 ["Nonce", txn.nonce],
-                ["Hash", status_str(status)]
+                ["Hash", status_str(envelope, status)?]
             );
             print_footer(status)
         }
@@ -135,8 +161,11 @@ fn print_create_txn(
                 "amount": txn.amount,
                 "hashlock": hex::encode(&txn.hashlock),
                 "timelock": txn.timelock,
+                "fee": cost.dc,
+                "fee_hnt": cost.hnt,
+                "implicit_burn": cost.implicit_burn,
                 "nonce": txn.nonce,
-                "hash": status_json(status),
+                "hash": status_json(envelope, status)?,
                 "txn": envelope.to_b64()?,
             });
             print_json(&table)
@@ -151,30 +180,48 @@ impl Redeem {
         let keypair = wallet.decrypt(password.as_bytes())?;
         let client = Client::new_with_base_url(api_url());
 
+        let mut txn = self.mk_txn(
+            keypair.pubkey_bin(),
+            &get_txn_fees(&client, &opts.vars_file)?,
+        )?;
+        txn.signature = sign_txn(&txn, &keypair)?;
+        let envelope = txn.in_envelope();
+        let cost = self.commit.validate(&client, &txn)?;
+
+        let status = self.commit.submit(&client, &envelope, None)?;
+
+        if self.hash {
+            println!("{}", status_str(&envelope, &status)?);
+        } else {
+            print_redeem_txn(&txn, &envelope, &cost, &status, opts.format)?;
+        }
+
+        self.commit.maybe_wait(&status, opts.format)
+    }
+
+    /// Construct the unsigned HTLC redeem transaction for the given
+    /// payee.
+    pub(crate) fn mk_txn(
+        &self,
+        payee: PubKeyBin,
+        fee_config: &TxnFeeConfig,
+    ) -> Result<BlockchainTxnRedeemHtlcV1> {
         let mut txn = BlockchainTxnRedeemHtlcV1 {
             fee: 0,
-            payee: keypair.pubkey_bin().into(),
+            payee: payee.into(),
             address: PubKeyBin::from_b58(&self.address)?.into(),
             preimage: self.preimage.clone().into_bytes(),
             signature: Vec::new(),
         };
-        txn.fee = txn.txn_fee(&get_txn_fees(&client)?)?;
-        txn.signature = txn.sign(&keypair)?;
-        let envelope = txn.in_envelope();
-
-        let status = if self.commit {
-            Some(client.submit_txn(&envelope)?)
-        } else {
-            None
-        };
-
-        print_redeem_txn(&txn, &envelope, &status, opts.format)
+        txn.fee = txn.txn_fee(fee_config)?;
+        Ok(txn)
     }
 }
 
 fn print_redeem_txn(
     txn: &BlockchainTxnRedeemHtlcV1,
     envelope: &BlockchainTxn,
+    cost: &FeeCost,
     status: &Option<PendingTxnStatus>,
     format: OutputFormat,
 ) -> Result {
@@ -185,7 +232,10 @@ fn print_redeem_txn(
                 ["Payee", PubKeyBin::from_vec(&txn.payee).to_b58()?],
                 ["Address", PubKeyBin::from_vec(&txn.address).to_b58()?],
                 ["Preimage", std::str::from_utf8(&txn.preimage)?],
-                ["Hash", status_str(status)]
+                ["Fee (DC)", cost.dc],
+                ["Fee (HNT)", cost.hnt],
+                ["Implicit Burn", cost.implicit_burn],
+                ["Hash", status_str(envelope, status)?]
             );
             print_footer(status)
         }
@@ -193,7 +243,10 @@ fn print_redeem_txn(
             let table = json!({
                 "address": PubKeyBin::from_vec(&txn.address).to_b58()?,
                 "payee": PubKeyBin::from_vec(&txn.payee).to_b58()?,
-                "hash": status_json(status),
+                "fee": cost.dc,
+                "fee_hnt": cost.hnt,
+                "implicit_burn": cost.implicit_burn,
+                "hash": status_json(envelope, status)?,
                 "txn": envelope.to_b64()?,
             });
             print_json(&table)
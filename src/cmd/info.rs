@@ -1,29 +1,29 @@
 use crate::{
-    cmd::{api_url, load_wallet, print_json, print_table, Opts, OutputFormat},
+    cmd::{api_url, load_wallet, print_json, print_table, Opts, OutputFormat, QrOpts},
     result::Result,
     wallet::Wallet,
 };
 use helium_api::{Account, Client, Hnt, Hst};
 use prettytable::Table;
-use qr2term::print_qr;
 use serde_json::json;
 use structopt::StructOpt;
 
 /// Get wallet information
 #[derive(Debug, StructOpt)]
 pub struct Cmd {
-    /// Display QR code for a given single wallet.
+    /// Display QR code for a given single wallet. Implied by --qr-out.
     #[structopt(long = "qr")]
     qr_code: bool,
+
+    #[structopt(flatten)]
+    qr: QrOpts,
 }
 
 impl Cmd {
     pub fn run(&self, opts: Opts) -> Result {
         let wallet = load_wallet(opts.files)?;
-        if self.qr_code {
-            let address = wallet.address()?;
-            print_qr(&address)?;
-            Ok(())
+        if self.qr_code || self.qr.qr_out.is_some() {
+            self.qr.output(&wallet.address()?)
         } else {
             let client = Client::new_with_base_url(api_url());
             let account = client.get_account(&wallet.address()?)?;
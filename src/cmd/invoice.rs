@@ -0,0 +1,261 @@
+use crate::{
+    amount::Amount,
+    api,
+    cmd::{
+        api_url, cached_oracle_price, data_dir, get_password, load_wallet, print_json, print_table,
+        request::{print_request, qr_payload},
+        Opts, OutputFormat, QrOpts,
+    },
+    invoice::{match_payments, Invoice, InvoiceBook, ReceivedPayment},
+    keypair::PubKeyBin,
+    payment_request::{now, PaymentRequest, RequestType},
+    result::Result,
+};
+use helium_api::Hnt;
+use prettytable::{format, Table};
+use serde_json::json;
+use structopt::StructOpt;
+
+/// The activity types that can pay an invoice
+const PAYMENT_TYPES: &str = "payment_v1,payment_v2";
+
+#[derive(Debug, StructOpt)]
+/// Issue numbered invoices as signed payment requests and check which
+/// of them have been paid
+pub enum Cmd {
+    Create(Create),
+    Check(Check),
+}
+
+#[derive(Debug, StructOpt)]
+/// Create a new invoice with the next invoice number. The invoice is a
+/// payment request signed by this wallet, shown as a QR code.
+pub struct Create {
+    /// Amount to invoice, in HNT unless a unit of bones, dc or usd is
+    /// given. DC and USD amounts are converted at the current oracle
+    /// price
+    #[structopt(long)]
+    amount: Amount,
+
+    /// A description of what the invoice is for
+    #[structopt(long)]
+    label: Option<String>,
+
+    /// Number of minutes after which the invoice expires
+    #[structopt(long)]
+    expires_in: Option<u64>,
+
+    /// The address the invoice is expected to be paid from. Only a
+    /// payment from this address pays the invoice
+    #[structopt(long)]
+    payer: Option<PubKeyBin>,
+
+    /// Encode the invoice in the QR code as a "helium:" URI instead of
+    /// as JSON
+    #[structopt(long)]
+    uri: bool,
+
+    #[structopt(flatten)]
+    qr: QrOpts,
+}
+
+#[derive(Debug, StructOpt)]
+/// Look up incoming payments for unpaid invoices and show the status of
+/// all invoices. Since payments have no memo, a payment pays the open
+/// invoice for exactly the same amount, preferring an invoice for its
+/// payer. A payment that could pay several invoices is reported and left
+/// unmatched.
+pub struct Check {
+    /// Only show the invoice with the given number
+    number: Option<u64>,
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        match self {
+            Cmd::Create(cmd) => cmd.run(opts),
+            Cmd::Check(cmd) => cmd.run(opts),
+        }
+    }
+}
+
+impl Create {
+    pub fn run(&self, opts: Opts) -> Result {
+        let password = get_password(false)?;
+        let wallet = load_wallet(opts.files)?;
+        let keypair = wallet.decrypt(password.as_bytes())?;
+
+        let bones = self.amount.to_bones(|| cached_oracle_price(&mut None))?;
+        let created = now()?;
+        let expires = self.expires_in.map(|minutes| created + minutes * 60);
+        let payer = self.payer.as_ref().map(|payer| payer.to_string());
+        let mut request = PaymentRequest::new(RequestType::Payment, wallet.pubkey_bin);
+        let book = InvoiceBook::open(&data_dir()?, &wallet.pubkey_bin)?;
+        let open: Vec<u64> = book
+            .invoices()?
+            .iter()
+            .filter(|invoice| {
+                invoice.payment.is_none()
+                    && !invoice.is_expired(created)
+                    && invoice.amount == bones
+                    && invoice.payer == payer
+            })
+            .map(|invoice| invoice.number)
+            .collect();
+        if !open.is_empty() {
+            eprintln!(
+                "warning: open invoices {:?} are for the same amount, a payment of this amount cannot be matched to one of them",
+                open
+            );
+        }
+        let invoice = book.create(|number| {
+            request.amount = Some(Amount::Bones(bones));
+            request.label = Some(match &self.label {
+                Some(label) => format!("Invoice {}: {}", number, label),
+                None => format!("Invoice {}", number),
+            });
+            request.expires = expires;
+            request.sign(&keypair)?;
+            Ok(Invoice {
+                number,
+                created,
+                amount: bones,
+                label: self.label.clone(),
+                expires,
+                payer: payer.clone(),
+                request: request.to_uri()?,
+                payment: None,
+            })
+        })?;
+
+        match opts.format {
+            OutputFormat::Table => print_request(&request, self.uri, &self.qr, opts.format),
+            OutputFormat::Json => {
+                self.qr.write(&qr_payload(&request, self.uri)?)?;
+                print_json(&json!({
+                    "number": invoice.number,
+                    "amount": Hnt::from_bones(invoice.amount),
+                    "label": invoice.label,
+                    "created": invoice.created,
+                    "expires": invoice.expires,
+                    "payer": invoice.payer,
+                    "uri": invoice.request,
+                    "request": request.to_json()?,
+                }))
+            }
+        }
+    }
+}
+
+impl Check {
+    pub fn run(&self, opts: Opts) -> Result {
+        let wallet = load_wallet(opts.files)?;
+        let book = InvoiceBook::open(&data_dir()?, &wallet.pubkey_bin)?;
+        let invoices = book.invoices()?;
+        if let Some(number) = self.number {
+            if !invoices.iter().any(|invoice| invoice.number == number) {
+                return Err(format!("No invoice with number {}", number).into());
+            }
+        }
+
+        // Only payments made after the oldest open invoice was created
+        // can pay an invoice
+        let since = invoices
+            .iter()
+            .filter(|invoice| invoice.payment.is_none())
+            .filter(|invoice| self.selects(invoice))
+            .map(|invoice| invoice.created)
+            .min();
+        let invoices = match since {
+            Some(since) => {
+                let address = wallet.address()?;
+                let payments: Vec<ReceivedPayment> = api::Client::new_with_base_url(api_url())
+                    .get_account_activity(&address, PAYMENT_TYPES, since)?
+                    .iter()
+                    .filter_map(|txn| ReceivedPayment::from_activity(&address, txn))
+                    .collect();
+                let (matches, invoices) = book.update(|invoices| {
+                    let matches = match_payments(invoices, &payments);
+                    Ok((matches, invoices.clone()))
+                })?;
+                for ambiguous in matches.ambiguous {
+                    eprintln!(
+                        "warning: payment {} of {} from {} could pay invoices {:?} and was not matched",
+                        ambiguous.payment.hash,
+                        Hnt::from_bones(ambiguous.payment.amount),
+                        ambiguous.payment.payer,
+                        ambiguous.invoices
+                    );
+                }
+                invoices
+            }
+            None => invoices,
+        };
+
+        let invoices: Vec<&Invoice> = invoices
+            .iter()
+            .filter(|invoice| self.selects(invoice))
+            .collect();
+        print_invoices(&invoices, now()?, opts.format)
+    }
+
+    fn selects(&self, invoice: &Invoice) -> bool {
+        match self.number {
+            Some(number) => number == invoice.number,
+            None => true,
+        }
+    }
+}
+
+fn invoice_status(invoice: &Invoice, now: u64) -> &'static str {
+    match &invoice.payment {
+        Some(_) => "paid",
+        None if invoice.is_expired(now) => "expired",
+        None => "open",
+    }
+}
+
+fn print_invoices(invoices: &[&Invoice], now: u64, format: OutputFormat) -> Result {
+    match format {
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+            table.set_titles(row![
+                "Number", "Label", "Amount", "Created", "Expires", "Status", "Payer", "Hash"
+            ]);
+            for invoice in invoices {
+                let payment = invoice.payment.as_ref();
+                table.add_row(row![
+                    invoice.number,
+                    invoice.label.clone().unwrap_or_default(),
+                    Hnt::from_bones(invoice.amount),
+                    invoice.created,
+                    invoice
+                        .expires
+                        .map_or_else(String::new, |expires| expires.to_string()),
+                    invoice_status(invoice, now),
+                    payment.map_or_else(String::new, |payment| payment.payer.clone()),
+                    payment.map_or_else(String::new, |payment| payment.hash.clone())
+                ]);
+            }
+            print_table(&table)
+        }
+        OutputFormat::Json => {
+            let mut json_invoices = Vec::with_capacity(invoices.len());
+            for invoice in invoices {
+                json_invoices.push(json!({
+                    "number": invoice.number,
+                    "label": invoice.label,
+                    "amount": Hnt::from_bones(invoice.amount),
+                    "created": invoice.created,
+                    "expires": invoice.expires,
+                    "status": invoice_status(invoice, now),
+                    "payer": invoice.payer,
+                    "payment": invoice.payment,
+                    "uri": invoice.request,
+                }));
+            }
+            print_json(&json!(json_invoices))
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use crate::{
+    cmd::{load_wallet, print_json, Opts, OutputFormat},
+    keypair::PubKeyBin,
+    result::Result,
+};
+use serde_json::json;
+use std::{fmt, str::FromStr};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+/// Work with the keys of a wallet
+pub enum Cmd {
+    ExportPublic(ExportPublic),
+}
+
+#[derive(Debug, StructOpt)]
+/// Export the public key of the wallet in a format that other tools
+/// can use ("peer-id", "pem", "ssh", "hex" or "jwk").
+pub struct ExportPublic {
+    /// The key format to export
+    #[structopt(long, default_value = "peer-id")]
+    format: KeyFormat,
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        match self {
+            Cmd::ExportPublic(cmd) => cmd.run(opts),
+        }
+    }
+}
+
+impl ExportPublic {
+    pub fn run(&self, opts: Opts) -> Result {
+        let wallet = load_wallet(opts.files)?;
+        print_key(&wallet.pubkey_bin, self.format, opts.format)
+    }
+}
+
+fn print_key(pubkey_bin: &PubKeyBin, key_format: KeyFormat, format: OutputFormat) -> Result {
+    let key = match key_format {
+        KeyFormat::PeerId => json!(pubkey_bin.to_peer_id()?),
+        KeyFormat::Pem => json!(pubkey_bin.to_pem()?),
+        KeyFormat::Ssh => json!(pubkey_bin.to_ssh()?),
+        KeyFormat::Hex => json!(pubkey_bin.to_hex()?),
+        KeyFormat::Jwk => pubkey_bin.to_jwk()?,
+    };
+    match format {
+        OutputFormat::Table => match key {
+            serde_json::Value::String(s) => {
+                println!("{}", s);
+                Ok(())
+            }
+            _ => print_json(&key),
+        },
+        OutputFormat::Json => {
+            let table = json!({
+                "address": pubkey_bin.to_string(),
+                "format": key_format.to_string(),
+                "key": key,
+            });
+            print_json(&table)
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum KeyFormat {
+    PeerId,
+    Pem,
+    Ssh,
+    Hex,
+    Jwk,
+}
+
+impl FromStr for KeyFormat {
+    type Err = Box<dyn std::error::Error>;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "peer-id" => Ok(KeyFormat::PeerId),
+            "pem" => Ok(KeyFormat::Pem),
+            "ssh" => Ok(KeyFormat::Ssh),
+            "hex" => Ok(KeyFormat::Hex),
+            "jwk" => Ok(KeyFormat::Jwk),
+            _ => Err(format!("Invalid key format {}", s).into()),
+        }
+    }
+}
+
+impl fmt::Display for KeyFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            KeyFormat::PeerId => "peer-id",
+            KeyFormat::Pem => "pem",
+            KeyFormat::Ssh => "ssh",
+            KeyFormat::Hex => "hex",
+            KeyFormat::Jwk => "jwk",
+        };
+        f.write_str(s)
+    }
+}
@@ -1,33 +1,56 @@
 use crate::{
-    keypair::PubKeyBin,
+    api::{self, PendingTxn, PendingTxnState},
+    cmd::vars::VarsSnapshot,
+    journal::{self, JournalStatus},
+    keypair::{Keypair, PubKeyBin},
     mnemonic,
+    nonce::{NonceKind, NonceReservation, NonceStore, RESERVATION_TIMEOUT},
+    policy,
+    qr::{self, QrErrorCorrection},
     result::Result,
-    traits::{TxnFeeConfig, B58},
+    traits::{
+        txn_fee::dc_to_bones, txn_validate::PayerState, txn_verify::ChainKeys, Sign, TxnEnvelope,
+        TxnFeeConfig, TxnHash, TxnValidate, B58, B64,
+    },
     wallet::Wallet,
 };
-use helium_api::{Client, PendingTxnStatus};
-use std::{env, fs, io, path::PathBuf};
+use helium_api::{BlockchainTxn, Client, Hnt, PendingTxnStatus, Txn};
+use std::{
+    env, fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    time::Duration,
+};
 use structopt::{clap::arg_enum, StructOpt};
 
 pub mod balance;
 pub mod burn;
+mod confirm;
 pub mod create;
+pub mod decrypt;
+pub mod encrypt;
+pub mod fees;
+pub mod history;
 pub mod hotspots;
 pub mod htlc;
 pub mod info;
+pub mod invoice;
+pub mod keys;
 pub mod multisig;
 pub mod onboard;
 pub mod oracle;
 pub mod oui;
 pub mod pay;
+pub mod pending;
 pub mod request;
 pub mod securities;
+pub mod txn;
 pub mod upgrade;
 pub mod vars;
 pub mod verify;
 
 arg_enum! {
-    #[derive(Debug)]
+    #[derive(Debug, Clone, Copy)]
     pub enum OutputFormat {
         Table,
         Json,
@@ -52,6 +75,223 @@ pub struct Opts {
                 case_insensitive = true,
                 default_value = "table")]
     format: OutputFormat,
+
+    /// Chain variables snapshot, as output by "vars snapshot", to use
+    /// instead of fetching chain variables from the API
+    #[structopt(long = "vars-file")]
+    vars_file: Option<PathBuf>,
+}
+
+/// The default number of seconds to wait for a committed transaction
+pub const DEFAULT_WAIT_TIMEOUT: u64 = 300;
+
+// Options for commands that commit a transaction to the API. This is
+// not a doc comment since structopt would use it as the about text of
+// every command that flattens it.
+#[derive(Debug, StructOpt)]
+pub struct CommitOpts {
+    /// Commit the transaction to the API
+    #[structopt(long)]
+    pub commit: bool,
+
+    /// Wait for a committed transaction to clear or fail, optionally
+    /// giving up after the given number of seconds
+    #[structopt(long, value_name = "timeout", requires = "commit")]
+    pub wait: Option<Option<u64>>,
+
+    /// Commit the transaction even if it is expected to fail
+    #[structopt(long, requires = "commit")]
+    pub force: bool,
+
+    /// Commit without asking for confirmation. Transactions above the
+    /// confirmation thresholds still need to be confirmed.
+    #[structopt(long, short = "y", requires = "commit")]
+    pub yes: bool,
+}
+
+impl CommitOpts {
+    /// Check the given transaction against the state of the account
+    /// paying for it and return the cost of its fee. Problems are
+    /// printed as warnings and prevent the transaction from being
+    /// committed unless --force is given.
+    pub fn validate<T: TxnValidate>(&self, client: &Client, txn: &T) -> Result<FeeCost> {
+        let cost = FeeCost::get(client, &txn.fee_payer()?, txn.total_fee())?;
+        self.check_problems(txn.validate(&cost.payer))?;
+        Ok(cost)
+    }
+
+    /// Validate like `validate` a transaction that fills a nonce gap,
+    /// so its nonce follows the given last nonce rather than the
+    /// speculative nonce of the paying account.
+    pub fn validate_after<T: TxnValidate>(
+        &self,
+        client: &Client,
+        txn: &T,
+        last_nonce: u64,
+    ) -> Result<FeeCost> {
+        let mut cost = FeeCost::get(client, &txn.fee_payer()?, txn.total_fee())?;
+        cost.payer.nonce = last_nonce;
+        self.check_problems(txn.validate(&cost.payer))?;
+        Ok(cost)
+    }
+
+    fn check_problems(&self, problems: Vec<String>) -> Result {
+        for problem in &problems {
+            eprintln!("warning: {}", problem);
+        }
+        if self.commit && !self.force && !problems.is_empty() {
+            return Err("Transaction would fail, use --force to submit it anyway".into());
+        }
+        Ok(())
+    }
+
+    /// Ask for confirmation to commit the given transaction when
+    /// committing. Returns an error if the transaction is not
+    /// confirmed.
+    pub fn confirm(&self, envelope: &BlockchainTxn) -> Result {
+        self.confirm_with(envelope, &[])
+    }
+
+    /// Ask for confirmation like `confirm`, including the given
+    /// details in the summary that is shown.
+    pub fn confirm_with(
+        &self,
+        envelope: &BlockchainTxn,
+        details: &[(&'static str, String)],
+    ) -> Result {
+        if self.commit {
+            confirm::confirm_txn(envelope, self.yes, details)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Submit the given transaction after confirmation when
+    /// committing. The nonce the transaction was built with, if any,
+    /// stays reserved if the submission succeeds.
+    pub fn submit(
+        &self,
+        client: &Client,
+        envelope: &BlockchainTxn,
+        nonce: Option<NonceReservation>,
+    ) -> Result<Option<PendingTxnStatus>> {
+        self.submit_with(client, envelope, nonce, &[])
+    }
+
+    /// Submit like `submit`, including the given details in the
+    /// confirmation summary.
+    pub fn submit_with(
+        &self,
+        client: &Client,
+        envelope: &BlockchainTxn,
+        nonce: Option<NonceReservation>,
+        details: &[(&'static str, String)],
+    ) -> Result<Option<PendingTxnStatus>> {
+        if !self.commit {
+            update_journal(envelope, JournalStatus::Preview, None);
+            return Ok(None);
+        }
+        self.confirm_with(envelope, details)?;
+        Ok(Some(submit_txn(client, envelope, nonce)?))
+    }
+
+    /// Wait for a submitted transaction to clear if waiting was
+    /// requested, print its final status and return an error if it
+    /// failed.
+    pub fn maybe_wait(&self, status: &Option<PendingTxnStatus>, format: OutputFormat) -> Result {
+        match (self.wait, status) {
+            (Some(timeout), Some(status)) => {
+                let timeout = timeout.unwrap_or(DEFAULT_WAIT_TIMEOUT);
+                wait_for_txn(&status.hash, Duration::from_secs(timeout), format)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+// Options for commands that show a QR code. Like CommitOpts this is not
+// a doc comment so it does not become the about text of commands.
+#[derive(Debug, StructOpt)]
+pub struct QrOpts {
+    /// Write a QR code to the given .png or .svg file. A QR code that
+    /// would be printed to the terminal is written to the file instead
+    #[structopt(long, value_name = "file")]
+    pub qr_out: Option<PathBuf>,
+
+    /// Minimum width and height in pixels of the written QR code
+    #[structopt(long, value_name = "pixels", default_value = "256")]
+    pub qr_size: u32,
+
+    /// Error correction level of the written QR code
+    #[structopt(long,
+                value_name = "level",
+                possible_values = &["low", "medium", "quartile", "high"],
+                case_insensitive = true,
+                default_value = "medium")]
+    pub qr_error_correction: QrErrorCorrection,
+}
+
+impl QrOpts {
+    /// Write the QR code for the given data to the output file, or
+    /// print it to the terminal if no output file was given.
+    pub fn output(&self, data: &str) -> Result {
+        if self.qr_out.is_some() {
+            self.write(data)
+        } else {
+            qr2term::print_qr(data)?;
+            Ok(())
+        }
+    }
+
+    /// Write the QR code for the given data to the output file, if one
+    /// was given.
+    pub fn write(&self, data: &str) -> Result {
+        match &self.qr_out {
+            Some(path) => qr::write_qr(path, data, self.qr_size, self.qr_error_correction),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Wait for the transaction with the given hash to clear or fail and
+/// print its final status. Returns an error if the transaction failed.
+pub fn wait_for_txn(hash: &str, timeout: Duration, format: OutputFormat) -> Result {
+    let client = api::Client::new_with_base_url(api_url());
+    let txn = client.wait_for_txn(hash, timeout)?;
+    print_pending_txn(&txn, format)?;
+    match txn.status {
+        PendingTxnState::Failed => Err(format!(
+            "Transaction {} failed: {}",
+            txn.hash,
+            txn.failed_reason.as_deref().unwrap_or("unknown reason")
+        )
+        .into()),
+        _ => Ok(()),
+    }
+}
+
+pub fn print_pending_txn(txn: &PendingTxn, format: OutputFormat) -> Result {
+    match format {
+        OutputFormat::Table => {
+            ptable!(
+                ["Key", "Value"],
+                ["Hash", txn.hash],
+                ["Type", txn.txn_type.as_deref().unwrap_or("unknown")],
+                ["Status", txn.status],
+                ["Failed Reason", txn.failed_reason.as_deref().unwrap_or("")]
+            );
+            Ok(())
+        }
+        OutputFormat::Json => {
+            let table = json!({
+                "hash": txn.hash,
+                "type": txn.txn_type,
+                "status": txn.status.to_string(),
+                "failed_reason": txn.failed_reason,
+            });
+            print_json(&table)
+        }
+    }
 }
 
 fn load_wallet(files: Vec<PathBuf>) -> Result<Wallet> {
@@ -92,6 +332,16 @@ fn api_url() -> String {
     env::var("HELIUM_API_URL").unwrap_or_else(|_| helium_api::DEFAULT_BASE_URL.to_string())
 }
 
+/// The directory used for local wallet state, like reserved nonces.
+pub fn data_dir() -> Result<PathBuf> {
+    match env::var("HELIUM_WALLET_DATA_DIR") {
+        Ok(dir) => Ok(PathBuf::from(dir)),
+        _ => dirs::data_dir()
+            .map(|dir| dir.join("helium-wallet"))
+            .ok_or_else(|| "Unable to determine data directory".into()),
+    }
+}
+
 fn collect_addresses(files: Vec<PathBuf>, mut addresses: Vec<String>) -> Result<Vec<String>> {
     // Any given addresses override _all_ the file parameters
     if addresses.is_empty() {
@@ -127,25 +377,234 @@ pub fn get_payer(staking_address: PubKeyBin, payer: &Option<String>) -> Result<O
     match payer {
         Some(s) if s == "staking" => Ok(Some(staking_address)),
         Some(s) => {
-            let address = PubKeyBin::from_b58(&s)?;
+            let address = PubKeyBin::from_b58(s)?;
             Ok(Some(address))
         }
         None => Ok(None),
     }
 }
 
-pub fn get_txn_fees(client: &Client) -> Result<TxnFeeConfig> {
-    let vars = client.get_vars()?;
-    if vars.contains_key("txn_fees") {
-        match vars["txn_fees"].as_bool() {
-            Some(true) => {
-                let config: TxnFeeConfig = serde_json::from_value(serde_json::Value::Object(vars))?;
-                Ok(config)
-            }
-            _ => Ok(TxnFeeConfig::legacy()),
+/// Get the chain variables from the given snapshot file, or from the
+/// API if no snapshot file is given.
+pub fn get_vars(
+    client: &Client,
+    vars_file: &Option<PathBuf>,
+) -> Result<serde_json::Map<String, serde_json::Value>> {
+    match vars_file {
+        Some(path) => Ok(VarsSnapshot::load(path)?.vars),
+        None => client.get_vars(),
+    }
+}
+
+/// Get the keys that control chain variables if the given transaction
+/// is a chain variable transaction, from the given snapshot file or
+/// from the API.
+pub fn get_chain_keys(
+    client: &Client,
+    vars_file: &Option<PathBuf>,
+    envelope: &BlockchainTxn,
+) -> Result<Option<ChainKeys>> {
+    match &envelope.txn {
+        Some(Txn::Vars(_)) => Ok(Some(ChainKeys::from_vars(&get_vars(client, vars_file)?)?)),
+        _ => Ok(None),
+    }
+}
+
+/// Get the last allocated OUI from the given snapshot file, or from
+/// the API if no snapshot file is given.
+pub fn get_last_oui(client: &Client, vars_file: &Option<PathBuf>) -> Result<u64> {
+    match vars_file {
+        Some(path) => Ok(VarsSnapshot::load(path)?.last_oui),
+        None => client.get_last_oui(),
+    }
+}
+
+pub fn get_txn_fees(client: &Client, vars_file: &Option<PathBuf>) -> Result<TxnFeeConfig> {
+    txn_fees(get_vars(client, vars_file)?)
+}
+
+/// The transaction fee configuration for the given chain variables
+pub fn txn_fees(vars: serde_json::Map<String, serde_json::Value>) -> Result<TxnFeeConfig> {
+    match vars.get("txn_fees").and_then(|v| v.as_bool()) {
+        Some(true) => {
+            let config: TxnFeeConfig = serde_json::from_value(serde_json::Value::Object(vars))?;
+            Ok(config)
+        }
+        _ => Ok(TxnFeeConfig::legacy()),
+    }
+}
+
+/// The cost of a transaction fee to the account paying it. Fees are
+/// paid in DC, and if the payer does not have enough DC the chain
+/// implicitly burns HNT to pay for them.
+pub struct FeeCost {
+    /// The fee in DC
+    pub dc: u64,
+    /// The HNT that would be burned for the fee at the current oracle
+    /// price
+    pub hnt: Hnt,
+    /// Whether the payer has too few DC, so HNT is burned for the fee
+    pub implicit_burn: bool,
+    payer: PayerState,
+}
+
+impl FeeCost {
+    /// Get the cost of the given DC fee to the given payer
+    pub fn get(client: &Client, payer: &PubKeyBin, dc: u64) -> Result<Self> {
+        let account = client.get_account(&payer.to_b58()?)?;
+        let bones = if dc == 0 {
+            0
+        } else {
+            let oracle_price = api::Client::new_with_base_url(api_url()).get_oracle_price()?;
+            dc_to_bones(dc, oracle_price)?
+        };
+        let implicit_burn = account.dc_balance < dc;
+        Ok(Self {
+            dc,
+            hnt: Hnt::from_bones(bones),
+            implicit_burn,
+            payer: PayerState {
+                balance: account.balance,
+                dc_balance: account.dc_balance,
+                sec_balance: account.sec_balance,
+                nonce: account.speculative_nonce,
+                sec_nonce: account.speculative_sec_nonce,
+                fee_bones: if implicit_burn { bones } else { 0 },
+            },
+        })
+    }
+}
+
+/// Add up the given amounts, returning an error if the total does not
+/// fit in a u64.
+pub fn total_amount<I: IntoIterator<Item = u64>>(amounts: I) -> Result<u64> {
+    amounts
+        .into_iter()
+        .try_fold(0u64, |total, amount| total.checked_add(amount))
+        .ok_or_else(|| "Total amount is too large".into())
+}
+
+/// Get the current oracle price, fetching it from the API only the
+/// first time it is needed.
+pub fn cached_oracle_price(price: &mut Option<u64>) -> Result<u64> {
+    match price {
+        Some(price) => Ok(*price),
+        None => {
+            let fetched = api::Client::new_with_base_url(api_url()).get_oracle_price()?;
+            *price = Some(fetched);
+            Ok(fetched)
+        }
+    }
+}
+
+/// Get the nonce for a new transaction by the given address. An
+/// explicitly given nonce is used as is. When the transaction is to be
+/// committed the nonce is reserved in the local nonce store, so that
+/// concurrent invocations do not reuse the speculative nonce from the
+/// API.
+pub fn get_nonce(
+    client: &Client,
+    address: &PubKeyBin,
+    kind: NonceKind,
+    nonce: Option<u64>,
+    commit: bool,
+) -> Result<NonceReservation> {
+    get_nonce_for(client, address, kind, nonce, commit, RESERVATION_TIMEOUT)
+}
+
+/// Get the nonce for a new transaction like `get_nonce`, keeping a
+/// reserved nonce for the given number of seconds.
+pub fn get_nonce_for(
+    client: &Client,
+    address: &PubKeyBin,
+    kind: NonceKind,
+    nonce: Option<u64>,
+    commit: bool,
+    timeout: u64,
+) -> Result<NonceReservation> {
+    let speculative_nonce = || -> Result<u64> {
+        let account = client.get_account(&address.to_b58()?)?;
+        match kind {
+            NonceKind::Hnt => Ok(account.speculative_nonce),
+            NonceKind::Security => Ok(account.speculative_sec_nonce),
+        }
+    };
+    match (nonce, commit) {
+        (Some(nonce), false) => Ok(NonceReservation::untracked(kind, nonce)),
+        (None, false) => Ok(NonceReservation::untracked(kind, speculative_nonce()? + 1)),
+        (nonce, true) => {
+            let store = NonceStore::open(&data_dir()?, address)?;
+            let speculative_nonce = match nonce {
+                Some(_) => 0,
+                None => speculative_nonce()?,
+            };
+            NonceReservation::reserve(store, kind, speculative_nonce, nonce, timeout)
         }
-    } else {
-        Ok(TxnFeeConfig::legacy())
+    }
+}
+
+/// Submit the given transaction, keeping the nonce reserved for it if
+/// any, recording the result in the local journal and recording the
+/// HNT it spends against the spending policy of its payer.
+pub fn submit_txn(
+    client: &Client,
+    envelope: &BlockchainTxn,
+    nonce: Option<NonceReservation>,
+) -> Result<PendingTxnStatus> {
+    let result = client.submit_txn(envelope);
+    match &result {
+        Ok(_) => update_journal(envelope, JournalStatus::Submitted, None),
+        Err(err) => update_journal(envelope, JournalStatus::Failed, Some(err.to_string())),
+    }
+    let status = result?;
+    if let Some(nonce) = nonce {
+        nonce.keep();
+    }
+    if let Err(err) = data_dir().and_then(|dir| policy::record_spend(&dir, envelope)) {
+        eprintln!("warning: failed to record spending: {}", err);
+    }
+    Ok(status)
+}
+
+/// Check the given transaction against the spending policy of the
+/// given signer, if it has one.
+pub fn check_policy(signer: &PubKeyBin, envelope: &BlockchainTxn) -> Result {
+    policy::check_policy(&data_dir()?, signer, envelope)
+}
+
+/// Record the HNT spent by the given transaction against the spending
+/// policy of its payer when it is signed. Unlike recording it when it
+/// is submitted this fails, so a daily limit is also enforced when the
+/// transaction is submitted elsewhere.
+pub fn record_signed_spend(envelope: &BlockchainTxn) -> Result {
+    policy::record_spend(&data_dir()?, envelope)
+}
+
+/// Sign the given transaction with the given keypair after checking it
+/// against the spending policy of the keypair.
+pub fn sign_txn<T: Sign + TxnEnvelope>(txn: &T, keypair: &Keypair) -> Result<Vec<u8>> {
+    let envelope = txn.in_envelope();
+    check_policy(&keypair.pubkey_bin(), &envelope)?;
+    record_journal(&keypair.pubkey_bin(), &envelope, JournalStatus::Built);
+    let signature = txn.sign(keypair)?;
+    record_signed_spend(&envelope)?;
+    Ok(signature)
+}
+
+/// Record the given transaction in the local journal of the given
+/// wallet. Failing to do so is only a warning since the transaction
+/// itself is not affected.
+pub fn record_journal(address: &PubKeyBin, envelope: &BlockchainTxn, status: JournalStatus) {
+    if let Err(err) = data_dir().and_then(|dir| journal::record(&dir, address, envelope, status)) {
+        eprintln!("warning: failed to record transaction in journal: {}", err);
+    }
+}
+
+/// Record a new status for the given transaction in the local journals
+/// of the wallets that recorded it.
+pub fn update_journal(envelope: &BlockchainTxn, status: JournalStatus, error: Option<String>) {
+    if let Err(err) = data_dir().and_then(|dir| journal::update(&dir, envelope, status, error)) {
+        eprintln!("warning: failed to record transaction in journal: {}", err);
     }
 }
 
@@ -153,11 +612,51 @@ pub fn open_output_file(filename: &PathBuf, create: bool) -> io::Result<fs::File
     fs::OpenOptions::new()
         .write(true)
         .create(true)
+        .truncate(true)
         .create_new(create)
         .open(filename)
 }
 
-pub fn get_file_extension(filename: &PathBuf) -> String {
+/// Read all data from the given file or from stdin if no file is
+/// given.
+pub fn read_input(input: &Option<PathBuf>) -> Result<Vec<u8>> {
+    let mut buffer = vec![];
+    match input {
+        Some(path) => fs::File::open(path)?.read_to_end(&mut buffer)?,
+        None => io::stdin().read_to_end(&mut buffer)?,
+    };
+    Ok(buffer)
+}
+
+/// Read a base64 encoded transaction. The given argument is either
+/// the transaction itself, a file containing the transaction, or "-"
+/// (or nothing) to read the transaction from stdin. The transaction
+/// may also be wrapped in a JSON object with a "txn" field, as output
+/// by most commands.
+pub fn read_txn(txn: &Option<String>) -> Result<BlockchainTxn> {
+    let data = match txn.as_deref() {
+        None | Some("-") => String::from_utf8(read_input(&None)?)?,
+        Some(path) if Path::new(path).is_file() => fs::read_to_string(path)?,
+        Some(encoded) => encoded.to_string(),
+    };
+    let data = data.trim();
+    match serde_json::from_str::<serde_json::Value>(data) {
+        Ok(json) => {
+            let encoded = json["txn"]
+                .as_str()
+                .ok_or("Expected a \"txn\" field with a base64 encoded transaction")?;
+            Ok(BlockchainTxn::from_b64(encoded)?)
+        }
+        Err(_) => Ok(BlockchainTxn::from_b64(data)?),
+    }
+}
+
+pub fn write_output(output: &Path, data: &[u8]) -> Result {
+    fs::write(output, data)?;
+    Ok(())
+}
+
+pub fn get_file_extension(filename: &Path) -> String {
     use std::ffi::OsStr;
     filename
         .extension()
@@ -184,10 +683,19 @@ pub fn print_table(table: &prettytable::Table) -> Result {
     Ok(())
 }
 
-pub fn status_str(status: &Option<PendingTxnStatus>) -> &str {
-    status.as_ref().map_or("none", |s| &s.hash)
+/// Get the hash of the given transaction. The hash is taken from the
+/// submission status when the transaction was submitted and computed
+/// locally otherwise.
+pub fn status_str(envelope: &BlockchainTxn, status: &Option<PendingTxnStatus>) -> Result<String> {
+    match status {
+        Some(s) => Ok(s.hash.clone()),
+        None => envelope.txn_hash()?.to_b64_url(),
+    }
 }
 
-pub fn status_json(status: &Option<PendingTxnStatus>) -> serde_json::Value {
-    status.as_ref().map_or(json!(null), |s| json!(s.hash))
+pub fn status_json(
+    envelope: &BlockchainTxn,
+    status: &Option<PendingTxnStatus>,
+) -> Result<serde_json::Value> {
+    Ok(json!(status_str(envelope, status)?))
 }
@@ -1,8 +1,11 @@
 use crate::{
-    cmd::{api_url, get_password, load_wallet, print_json, status_json, Opts},
+    cmd::{
+        api_url, get_password, load_wallet, print_json, sign_txn, status_json,
+        txn::artifact::Artifact, CommitOpts, Opts,
+    },
     keypair::Keypair,
     result::Result,
-    traits::{Sign, ToJson, B64},
+    traits::{ToJson, B64},
 };
 use helium_api::{BlockchainTxn, Client, PendingTxnStatus, Txn};
 use serde::{Deserialize, Serialize};
@@ -46,9 +49,8 @@ pub struct Combine {
     #[structopt(long = "proof", name = "PROOF FILE", number_of_values(1))]
     proofs: Vec<PathBuf>,
 
-    /// Commit the combined transaction to the API
-    #[structopt(long)]
-    commit: bool,
+    #[structopt(flatten)]
+    commit: CommitOpts,
 }
 
 impl Cmd {
@@ -61,11 +63,6 @@ impl Cmd {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub(crate) struct Artifact {
-    txn: String,
-}
-
 enum ProofType {
     KeyProof,
     Proof,
@@ -79,7 +76,7 @@ struct Proofs {
 
 impl Inspect {
     pub fn run(&self, _opts: Opts) -> Result {
-        let txn = Artifact::load_txn(&self.artifact)?;
+        let txn = Artifact::load(&self.artifact)?.to_txn()?;
         print_txn(&txn, &None)
     }
 }
@@ -90,7 +87,7 @@ impl Prove {
         let wallet = load_wallet(opts.files)?;
         let keypair = wallet.decrypt(password.as_bytes())?;
 
-        let txn = Artifact::load_txn(&self.artifact)?;
+        let txn = Artifact::load(&self.artifact)?.to_txn()?;
         let mut proofs = Proofs::new();
         let proof_type = if self.key {
             ProofType::KeyProof
@@ -103,8 +100,8 @@ impl Prove {
 }
 
 impl Combine {
-    pub fn run(&self, _opts: Opts) -> Result {
-        let mut envelope = Artifact::load_txn(&self.artifact)?;
+    pub fn run(&self, opts: Opts) -> Result {
+        let mut envelope = Artifact::load(&self.artifact)?.to_txn()?;
         // Load proofs and key_proof maps from txn
         let mut combined_proofs = Proofs::from_txn(&envelope)?;
         for path in &self.proofs {
@@ -112,22 +109,16 @@ impl Combine {
             combined_proofs.merge_proofs(&proofs)?;
         }
         combined_proofs.apply(&mut envelope)?;
-        let status = if self.commit {
-            let client = Client::new_with_base_url(api_url());
-            Some(client.submit_txn(&envelope)?)
-        } else {
-            None
-        };
-        print_txn(&envelope, &status)
+        let client = Client::new_with_base_url(api_url());
+        let status = self.commit.submit(&client, &envelope, None)?;
+        print_txn(&envelope, &status)?;
+        self.commit.maybe_wait(&status, opts.format)
     }
 }
 
 fn print_txn(envelope: &BlockchainTxn, status: &Option<PendingTxnStatus>) -> Result {
-    let mut json = match &envelope.txn {
-        Some(Txn::Vars(t)) => t.to_json()?,
-        _ => return Err("Unsupported transaction for multisig".into()),
-    };
-    json["hash"] = status_json(status);
+    let mut json = envelope.to_json()?;
+    json["hash"] = status_json(envelope, status)?;
     json["txn"] = envelope.to_b64()?.into();
     print_json(&json)
 }
@@ -168,11 +159,11 @@ impl Proofs {
             Some(Txn::Vars(t)) => {
                 t.multi_key_proofs = Vec::with_capacity(self.key_proofs.len());
                 for signature in &self.key_proofs {
-                    t.multi_key_proofs.push(Vec::<u8>::from_b64(&signature)?);
+                    t.multi_key_proofs.push(Vec::<u8>::from_b64(signature)?);
                 }
                 t.multi_proofs = Vec::with_capacity(self.proofs.len());
                 for signature in &self.proofs {
-                    t.multi_proofs.push(Vec::<u8>::from_b64(&signature)?);
+                    t.multi_proofs.push(Vec::<u8>::from_b64(signature)?);
                 }
             }
             _ => return Err("Invalid transaction for proof".into()),
@@ -188,7 +179,7 @@ impl Proofs {
     ) -> Result {
         match &envelope.txn {
             Some(Txn::Vars(t)) => {
-                let signature = t.sign(&keypair)?.to_b64()?;
+                let signature = sign_txn(t, keypair)?.to_b64()?;
                 match proof_type {
                     ProofType::KeyProof => self.key_proofs.push(signature),
                     ProofType::Proof => self.proofs.push(signature),
@@ -212,19 +203,3 @@ impl Proofs {
         self.key_proofs.dedup_by(|a, b| a == b)
     }
 }
-
-impl Artifact {
-    fn load_txn(path: &PathBuf) -> Result<BlockchainTxn> {
-        let file = File::open(path)?;
-        let artifact: Artifact = serde_json::from_reader(&file)?;
-        artifact.to_txn()
-    }
-
-    pub fn from_txn(txn: &BlockchainTxn) -> Result<Self> {
-        Ok(Self { txn: txn.to_b64()? })
-    }
-
-    pub fn to_txn(&self) -> Result<BlockchainTxn> {
-        Ok(BlockchainTxn::from_b64(&self.txn)?)
-    }
-}
@@ -1,8 +1,11 @@
 use crate::{
-    cmd::{api_url, get_password, load_wallet, print_json, status_json, Opts, OutputFormat},
+    cmd::{
+        api_url, get_password, load_wallet, print_footer, print_json, sign_txn, status_json,
+        status_str, CommitOpts, FeeCost, Opts, OutputFormat,
+    },
     result::Result,
     staking,
-    traits::{Sign, TxnPayer, B64},
+    traits::{ToJson, TxnPayer, TxnVerify, B64},
 };
 use helium_api::{BlockchainTxn, PendingTxnStatus, Txn};
 use serde_json::json;
@@ -25,8 +28,8 @@ pub struct Cmd {
     #[structopt(long)]
     onboarding: Option<String>,
 
-    #[structopt(long)]
-    commit: bool,
+    #[structopt(flatten)]
+    commit: CommitOpts,
 }
 
 impl Cmd {
@@ -37,13 +40,16 @@ impl Cmd {
         // let staking_address = get_staking_address()?;
         // Now decode the given transaction
         let mut envelope = BlockchainTxn::from_b64(&self.read_txn()?)?;
+        // Refuse to co-sign unless the hotspot actually signed this
+        // transaction
+        envelope.verify_signature("gateway")?;
 
         match &mut envelope.txn {
             Some(Txn::AddGateway(t)) => {
-                t.owner_signature = t.sign(&keypair)?;
+                t.owner_signature = sign_txn(t, &keypair)?;
             }
             Some(Txn::AssertLocation(t)) => {
-                t.owner_signature = t.sign(&keypair)?;
+                t.owner_signature = sign_txn(t, &keypair)?;
             }
             _ => return Err("Unsupported transaction for onboarding".into()),
         };
@@ -66,23 +72,20 @@ impl Cmd {
                 }
                 _ => Err("Unsupported transaction for onboarding"),
             },
-            Some(_maker_key) => {
-                if self.onboarding.is_none() {
-                    Err("Staking server requires an onboarding key")
-                } else {
-                    let onboarding_key = self.onboarding.as_ref().unwrap().replace("\"", "");
+            Some(_maker_key) => match &self.onboarding {
+                None => Err("Staking server requires an onboarding key"),
+                Some(onboarding) => {
+                    let onboarding_key = onboarding.replace('"', "");
                     Ok(staking_client.sign(&onboarding_key, &envelope)?)
                 }
-            }
+            },
             None => Ok(envelope),
         }?;
 
-        let status = if self.commit {
-            Some(api_client.submit_txn(&envelope)?)
-        } else {
-            None
-        };
-        print_txn(&envelope, &status, opts.format)
+        let cost = self.commit.validate(&api_client, &envelope)?;
+        let status = self.commit.submit(&api_client, &envelope, None)?;
+        print_txn(&envelope, &cost, &status, opts.format)?;
+        self.commit.maybe_wait(&status, opts.format)
     }
 
     fn read_txn(&self) -> Result<String> {
@@ -99,16 +102,33 @@ impl Cmd {
 
 fn print_txn(
     envelope: &BlockchainTxn,
+    cost: &FeeCost,
     status: &Option<PendingTxnStatus>,
     format: OutputFormat,
 ) -> Result {
     let encoded = envelope.to_b64()?;
     match format {
-        OutputFormat::Table => Err("Table format not supported for transaction output".into()),
+        OutputFormat::Table => {
+            ptable!(
+                ["Key", "Value"],
+                [
+                    "Type",
+                    envelope.to_json()?["type"].as_str().unwrap_or("unknown")
+                ],
+                ["Fee (DC)", cost.dc],
+                ["Fee (HNT)", cost.hnt],
+                ["Implicit Burn", cost.implicit_burn],
+                ["Hash", status_str(envelope, status)?]
+            );
+            print_footer(status)
+        }
         OutputFormat::Json => {
             let table = json!({
                 "txn": encoded,
-                "hash": status_json(status)
+                "fee": cost.dc,
+                "fee_hnt": cost.hnt,
+                "implicit_burn": cost.implicit_burn,
+                "hash": status_json(envelope, status)?
             });
             print_json(&table)
         }
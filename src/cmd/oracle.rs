@@ -1,16 +1,16 @@
 use crate::{
     cmd::{
-        api_url, get_password, load_wallet, print_footer, print_json, status_json, status_str,
-        Opts, OutputFormat,
+        api_url, get_password, load_wallet, print_footer, print_json, sign_txn, status_json,
+        status_str, CommitOpts, Opts, OutputFormat,
     },
     result::Result,
-    traits::{Sign, TxnEnvelope, B64},
+    traits::{TxnEnvelope, B64},
 };
 use helium_api::{BlockchainTxn, BlockchainTxnPriceOracleV1, Client, PendingTxnStatus};
 use rust_decimal::{prelude::*, Decimal};
 use serde::Serialize;
 use serde_json::json;
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
 use structopt::StructOpt;
 
 /// Report an oracle price to the blockchain
@@ -33,9 +33,8 @@ pub struct Report {
     #[structopt(long)]
     block: Block,
 
-    /// Commit the oracle price report to the API
-    #[structopt(long)]
-    commit: bool,
+    #[structopt(flatten)]
+    commit: CommitOpts,
 }
 
 impl Cmd {
@@ -60,15 +59,13 @@ impl Report {
             block_height: self.block.to_block(),
             signature: Vec::new(),
         };
-        txn.signature = txn.sign(&keypair)?;
+        txn.signature = sign_txn(&txn, &keypair)?;
         let envelope = txn.in_envelope();
-        let status = if self.commit {
-            Some(client.submit_txn(&envelope)?)
-        } else {
-            None
-        };
+        let status = self.commit.submit(&client, &envelope, None)?;
+
+        print_txn(&txn, &envelope, &status, opts.format)?;
 
-        print_txn(&txn, &envelope, &status, opts.format)
+        self.commit.maybe_wait(&status, opts.format)
     }
 }
 
@@ -85,7 +82,7 @@ fn print_txn(
                 ["Key", "Value"],
                 ["Block Height", txn.block_height],
                 ["Price", Price::from_millis(txn.price)],
-                ["Hash", status_str(status)]
+                ["Hash", status_str(envelope, status)?]
             );
 
             print_footer(status)
@@ -95,7 +92,7 @@ fn print_txn(
                 "price": txn.price,
                 "block_height": txn.block_height,
                 "txn": encoded,
-                "hash": status_json(status)
+                "hash": status_json(envelope, status)?
             });
             print_json(&table)
         }
@@ -199,8 +196,8 @@ impl FromStr for Price {
     }
 }
 
-impl ToString for Price {
-    fn to_string(&self) -> String {
-        self.0.to_string()
+impl fmt::Display for Price {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
     }
 }
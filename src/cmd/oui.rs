@@ -1,11 +1,11 @@
 use crate::{
     cmd::{
-        api_url, get_password, get_txn_fees, load_wallet, print_footer, print_json, status_json,
-        status_str, Opts, OutputFormat,
+        api_url, get_last_oui, get_password, get_txn_fees, load_wallet, print_footer, print_json,
+        sign_txn, status_json, status_str, CommitOpts, FeeCost, Opts, OutputFormat,
     },
     keypair::PubKeyBin,
     result::Result,
-    traits::{Sign, TxnEnvelope, TxnFee, TxnStakingFee, B64},
+    traits::{TxnEnvelope, TxnFee, TxnStakingFee, B64},
 };
 use helium_api::{BlockchainTxn, BlockchainTxnOuiV1, Client, PendingTxnStatus, Txn};
 use serde_json::json;
@@ -20,7 +20,10 @@ pub enum Cmd {
 
 /// Allocates an Organizational Unique Identifier (OUI) which
 /// identifies endpoints for packets to sent to The transaction is not
-/// submitted to the system unless the '--commit' option is given.
+/// submitted to the system unless the '--commit' option is given. If
+/// the staking server is used as the payer the transaction must first
+/// be submitted to the staking server for signing and the result
+/// submitted to the API.
 #[derive(Debug, StructOpt)]
 pub struct Create {
     /// The address(es) of the router to send packets to
@@ -41,28 +44,23 @@ pub struct Create {
     #[structopt(long)]
     payer: Option<PubKeyBin>,
 
-    /// Commit the transaction to the API. If the staking server is
-    /// used as the payer the transaction must first be submitted to
-    /// the staking server for signing and the result submitted ot the
-    /// API.
-    #[structopt(long)]
-    commit: bool,
+    #[structopt(flatten)]
+    commit: CommitOpts,
 }
 
 /// Submits a given base64 oui transaction to the API. This command
 /// can be used when this wallet is not the payer of the oui
-/// transaction.
+/// transaction. If the staking server is used as the payer the
+/// transaction is first submitted to the staking server for signing and
+/// the result submitted to the API.
 #[derive(Debug, StructOpt)]
 pub struct Submit {
     /// Base64 encoded transaction to submit.
     #[structopt(name = "TRANSACTION")]
     transaction: String,
 
-    /// Commit the payment to the API. If the staking server is used
-    /// as the payer the transaction is first submitted to the staking
-    /// server for signing and the result submitted ot the API.
-    #[structopt(long)]
-    commit: bool,
+    #[structopt(flatten)]
+    commit: CommitOpts,
 }
 
 impl Cmd {
@@ -92,7 +90,7 @@ impl Create {
                 .collect(),
             owner: keypair.pubkey_bin().into(),
             payer: self.payer.map_or(vec![], |v| v.to_vec()),
-            oui: api_client.get_last_oui()?,
+            oui: get_last_oui(&api_client, &opts.vars_file)?,
             fee: 0,
             staking_fee: 1,
             owner_signature: vec![],
@@ -100,25 +98,24 @@ impl Create {
             requested_subnet_size: self.subnet_size,
             filter: base64::decode(&self.filter)?,
         };
-        txn.fee = txn.txn_fee(&get_txn_fees(&api_client)?)?;
-        txn.staking_fee = txn.txn_staking_fee(&get_txn_fees(&api_client)?)?;
-        txn.owner_signature = txn.sign(&keypair)?;
+        let fee_config = get_txn_fees(&api_client, &opts.vars_file)?;
+        txn.fee = txn.txn_fee(&fee_config)?;
+        txn.staking_fee = txn.txn_staking_fee(&fee_config)?;
+        txn.owner_signature = sign_txn(&txn, &keypair)?;
         let envelope = txn.in_envelope();
+        let cost = self.commit.validate(&api_client, &txn)?;
 
         match self.payer {
             key if key == Some(wallet_key) || key.is_none() => {
                 // Payer is the wallet submit if ready to commit
-                let status = if self.commit {
-                    Some(api_client.submit_txn(&envelope)?)
-                } else {
-                    None
-                };
-                print_txn(&txn, &envelope, &status, opts.format)
+                let status = self.commit.submit(&api_client, &envelope, None)?;
+                print_txn(&txn, &envelope, &cost, &status, opts.format)?;
+                self.commit.maybe_wait(&status, opts.format)
             }
             _ => {
                 // Payer is something else.
                 // can't commit this transaction but we can display it
-                print_txn(&txn, &envelope, &None, opts.format)
+                print_txn(&txn, &envelope, &cost, &None, opts.format)
             }
         }
     }
@@ -129,12 +126,10 @@ impl Submit {
         let envelope = BlockchainTxn::from_b64(&self.transaction)?;
         if let Some(Txn::Oui(t)) = envelope.txn.clone() {
             let api_client = helium_api::Client::new_with_base_url(api_url());
-            let status = if self.commit {
-                Some(api_client.submit_txn(&envelope)?)
-            } else {
-                None
-            };
-            print_txn(&t, &envelope, &status, opts.format)
+            let cost = self.commit.validate(&api_client, &t)?;
+            let status = self.commit.submit(&api_client, &envelope, None)?;
+            print_txn(&t, &envelope, &cost, &status, opts.format)?;
+            self.commit.maybe_wait(&status, opts.format)
         } else {
             Err("Invalid OUI transaction".into())
         }
@@ -144,6 +139,7 @@ impl Submit {
 fn print_txn(
     txn: &BlockchainTxnOuiV1,
     envelope: &BlockchainTxn,
+    cost: &FeeCost,
     status: &Option<PendingTxnStatus>,
     format: OutputFormat,
 ) -> Result {
@@ -162,7 +158,10 @@ fn print_txn(
                         .collect::<Vec<String>>()
                         .join("\n")
                 ],
-                ["Hash", status_str(status)]
+                ["Fee (DC)", cost.dc],
+                ["Fee (HNT)", cost.hnt],
+                ["Implicit Burn", cost.implicit_burn],
+                ["Hash", status_str(envelope, status)?]
             );
 
             print_footer(status)
@@ -176,7 +175,10 @@ fn print_txn(
                     .map(|v| PubKeyBin::from_vec(&v).to_string())
                     .collect::<Vec<String>>(),
                 "requested_subnet_size": txn.requested_subnet_size,
-                "hash": status_json(status),
+                "fee": cost.dc,
+                "fee_hnt": cost.hnt,
+                "implicit_burn": cost.implicit_burn,
+                "hash": status_json(envelope, status)?,
                 "txn": envelope.to_b64()?,
             });
 
@@ -0,0 +1,236 @@
+use crate::{
+    amount::Amount,
+    cmd::{
+        cached_oracle_price, get_file_extension, print_footer, print_json, print_table,
+        status_json, status_str, total_amount, FeeCost, OutputFormat,
+    },
+    keypair::PubKeyBin,
+    result::Result,
+    traits::{B58, B64},
+};
+use helium_api::{BlockchainTxn, BlockchainTxnPaymentV2, Hnt, Payment, PendingTxnStatus};
+use prettytable::{format, Table};
+use serde_derive::Deserialize;
+use serde_json::json;
+use std::{fs, path::PathBuf};
+
+/// A single payee in a batch. Rows in the payments file for the same
+/// payee are combined into one payment.
+pub struct BatchPayment {
+    pub payee: PubKeyBin,
+    pub amount: u64,
+    pub rows: Vec<usize>,
+}
+
+impl BatchPayment {
+    pub fn to_payment(&self) -> Payment {
+        Payment {
+            payee: self.payee.into(),
+            amount: self.amount,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct PayeeRecord {
+    address: String,
+    amount: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct CsvRecord {
+    address: String,
+    amount: String,
+}
+
+/// Read and validate all payments in the given CSV or JSON file. Amounts
+/// are in HNT unless a unit is given, and DC and USD amounts are
+/// converted at the current oracle price. All invalid rows are reported
+/// at once.
+pub fn read_payments(path: &PathBuf) -> Result<Vec<BatchPayment>> {
+    let records = match get_file_extension(path).to_lowercase().as_str() {
+        "json" => read_json(path)?,
+        "csv" => read_csv(path)?,
+        ext => return Err(format!("Unsupported payments file type \"{}\"", ext).into()),
+    };
+
+    let mut payments: Vec<BatchPayment> = Vec::with_capacity(records.len());
+    let mut errors = vec![];
+    let mut oracle_price = None;
+    for (row, record) in records {
+        match parse_record(&record, &mut oracle_price) {
+            Ok((payee, amount)) => match payments.iter_mut().find(|p| p.payee == payee) {
+                Some(payment) => match payment.amount.checked_add(amount) {
+                    Some(total) => {
+                        payment.amount = total;
+                        payment.rows.push(row);
+                    }
+                    None => errors.push(format!(
+                        "row {}: total amount for payee {} is too large",
+                        row, record.address
+                    )),
+                },
+                None => payments.push(BatchPayment {
+                    payee,
+                    amount,
+                    rows: vec![row],
+                }),
+            },
+            Err(err) => errors.push(format!("row {}: {}", row, err)),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(format!("Invalid payments:\n  {}", errors.join("\n  ")).into());
+    }
+    if payments.is_empty() {
+        return Err("No payments found".into());
+    }
+    total_amount(payments.iter().map(|p| p.amount))?;
+    Ok(payments)
+}
+
+fn read_csv(path: &PathBuf) -> Result<Vec<(usize, PayeeRecord)>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_path(path)?;
+    let headers = reader.headers()?.clone();
+    let mut records = vec![];
+    for result in reader.records() {
+        let record = result?;
+        let row = record.position().map_or(0, |p| p.line() as usize);
+        let csv_record: CsvRecord = record.deserialize(Some(&headers))?;
+        records.push((
+            row,
+            PayeeRecord {
+                address: csv_record.address,
+                amount: csv_record.amount.into(),
+            },
+        ));
+    }
+    Ok(records)
+}
+
+fn read_json(path: &PathBuf) -> Result<Vec<(usize, PayeeRecord)>> {
+    let records: Vec<PayeeRecord> = serde_json::from_slice(&fs::read(path)?)?;
+    Ok(records
+        .into_iter()
+        .enumerate()
+        .map(|(index, record)| (index + 1, record))
+        .collect())
+}
+
+fn parse_record(record: &PayeeRecord, oracle_price: &mut Option<u64>) -> Result<(PubKeyBin, u64)> {
+    let payee = PubKeyBin::from_b58(&record.address)
+        .map_err(|_| format!("invalid address \"{}\"", record.address))?;
+    let amount = match &record.amount {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Number(n) => n.to_string(),
+        _ => return Err("invalid amount".into()),
+    };
+    let amount = amount
+        .parse::<Amount>()?
+        .to_bones(|| cached_oracle_price(oracle_price))?;
+    if amount == 0 {
+        return Err("amount must be greater than zero".into());
+    }
+    Ok((payee, amount))
+}
+
+pub fn print_batch(
+    payments: &[BatchPayment],
+    max_payments: usize,
+    txns: &[(
+        BlockchainTxnPaymentV2,
+        BlockchainTxn,
+        Option<PendingTxnStatus>,
+    )],
+    cost: &FeeCost,
+    format: OutputFormat,
+) -> Result {
+    let total = total_amount(payments.iter().map(|p| p.amount))?;
+    let txn_hash = |index: usize| -> Result<Option<String>> {
+        match txns.get(index / max_payments) {
+            Some((_, envelope, status)) => Ok(Some(status_str(envelope, status)?)),
+            None => Ok(None),
+        }
+    };
+    let rows_str = |payment: &BatchPayment| -> String {
+        let rows: Vec<String> = payment.rows.iter().map(|row| row.to_string()).collect();
+        rows.join(", ")
+    };
+    match format {
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+            table.set_titles(row!["Nonce", "Payments", "Amount", "Fee", "Hash"]);
+            for (txn, envelope, status) in txns {
+                let amount = total_amount(txn.payments.iter().map(|p| p.amount))?;
+                table.add_row(row![
+                    txn.nonce,
+                    txn.payments.len(),
+                    Hnt::from_bones(amount),
+                    txn.fee,
+                    status_str(envelope, status)?
+                ]);
+            }
+            print_table(&table)?;
+
+            let mut table = Table::new();
+            table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+            table.set_titles(row!["Row", "Payee", "Amount", "Hash"]);
+            for (index, payment) in payments.iter().enumerate() {
+                table.add_row(row![
+                    rows_str(payment),
+                    payment.payee.to_b58()?,
+                    Hnt::from_bones(payment.amount),
+                    txn_hash(index)?.unwrap_or_else(|| "not submitted".to_string())
+                ]);
+            }
+            print_table(&table)?;
+
+            ptable!(
+                ["Key", "Value"],
+                ["Total Amount", Hnt::from_bones(total)],
+                ["Total Fee (DC)", cost.dc],
+                ["Total Fee (HNT)", cost.hnt],
+                ["Implicit Burn", cost.implicit_burn]
+            );
+            if txns.iter().all(|(_, _, status)| status.is_none()) {
+                print_footer(&None)?;
+            }
+            Ok(())
+        }
+        OutputFormat::Json => {
+            let mut json_txns = Vec::with_capacity(txns.len());
+            for (txn, envelope, status) in txns {
+                let amount = total_amount(txn.payments.iter().map(|p| p.amount))?;
+                json_txns.push(json!({
+                    "nonce": txn.nonce,
+                    "payments": txn.payments.len(),
+                    "amount": Hnt::from_bones(amount),
+                    "fee": txn.fee,
+                    "hash": status_json(envelope, status)?,
+                    "txn": envelope.to_b64()?,
+                }));
+            }
+            let mut json_payments = Vec::with_capacity(payments.len());
+            for (index, payment) in payments.iter().enumerate() {
+                json_payments.push(json!({
+                    "rows": payment.rows,
+                    "payee": payment.payee.to_b58()?,
+                    "amount": Hnt::from_bones(payment.amount),
+                    "hash": txn_hash(index)?,
+                }));
+            }
+            let table = json!({
+                "transactions": json_txns,
+                "payments": json_payments,
+                "total_amount": Hnt::from_bones(total),
+                "total_fee": cost.dc,
+                "total_fee_hnt": cost.hnt,
+                "implicit_burn": cost.implicit_burn,
+            });
+            print_json(&table)
+        }
+    }
+}
@@ -0,0 +1,475 @@
+use crate::{
+    amount::Amount,
+    cmd::{
+        api_url, cached_oracle_price, get_nonce, get_password, get_txn_fees, get_vars, load_wallet,
+        print_footer, print_json, print_table, sign_txn, status_json, status_str, submit_txn,
+        total_amount, txn_fees, update_journal, CommitOpts, FeeCost, Opts, OutputFormat,
+    },
+    journal::JournalStatus,
+    keypair::PubKeyBin,
+    nonce::NonceKind,
+    payment_request::{PaymentRequest, RequestType},
+    result::Result,
+    traits::{txn_fee::dc_to_bones, TxnEnvelope, TxnFee, TxnFeeConfig, B58, B64},
+};
+use helium_api::{BlockchainTxn, BlockchainTxnPaymentV2, Client, Hnt, Payment, PendingTxnStatus};
+use prettytable::Table;
+use serde_json::json;
+use std::{path::PathBuf, str::FromStr};
+use structopt::StructOpt;
+
+mod batch;
+
+/// The maximum number of payments in a single transaction if the chain
+/// does not define it
+const DEFAULT_MAX_PAYMENTS: usize = 50;
+/// The maximum number of fee calculations when sending the remaining
+/// balance of a wallet
+const MAX_SWEEP_ITERATIONS: usize = 10;
+
+#[derive(Debug, StructOpt)]
+/// Send one or more payments to given addresses. Note that HNT only
+/// goes to 8 decimals of precision. The payment is not submitted to
+/// the system unless the '--commit' option is given.
+pub struct Cmd {
+    /// Address and amount to send in <address>=<amount> format. The
+    /// amount is in HNT unless a unit of bones, dc or usd is given.
+    #[structopt(
+        long = "payee",
+        short = "p",
+        name = "payee=hnt",
+        required_unless_one = &["from-file", "sweep", "request"]
+    )]
+    payees: Vec<Payee>,
+
+    /// Send the entire remaining balance of the wallet, less the
+    /// transaction fee, to the given address. This is the same as
+    /// "--payee <address>=max".
+    #[structopt(long, conflicts_with = "from-file")]
+    sweep: Option<String>,
+
+    /// Read payments from a CSV file with "address" and "amount"
+    /// columns, or a JSON file with a list of objects with "address"
+    /// and "amount" fields. Amounts take the same units as payee
+    /// amounts. Payments to the same address are combined and the
+    /// payments are split over as many transactions as the chain
+    /// allows, using consecutive nonces.
+    #[structopt(long = "from-file", name = "from-file", conflicts_with = "payee=hnt")]
+    from_file: Option<PathBuf>,
+
+    /// Pay a payment request, given as a "helium:" URI, as the JSON of
+    /// a request, or as the path of a file containing either. The
+    /// payee and amount are taken from the request.
+    #[structopt(long, conflicts_with_all = &["from-file", "sweep", "payee=hnt"])]
+    request: Option<String>,
+
+    /// The address the payment request is expected to come from. The
+    /// request is only paid if it is signed by this address, and only
+    /// then is the requester shown as verified.
+    #[structopt(long, requires = "request")]
+    expect_requester: Option<PubKeyBin>,
+
+    /// Manually set DC fee to pay for the transaction
+    #[structopt(long)]
+    fee: Option<u64>,
+
+    /// Manually set the nonce to use for the transaction
+    #[structopt(long)]
+    pub(crate) nonce: Option<u64>,
+
+    #[structopt(flatten)]
+    commit: CommitOpts,
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        if let Some(path) = &self.from_file {
+            return self.run_batch(path, opts);
+        }
+        let request = self.payment_request()?;
+        let details = match &request {
+            Some(request) => request.details(self.expect_requester.as_ref())?,
+            None => vec![],
+        };
+
+        let password = get_password(false)?;
+        let wallet = load_wallet(opts.files)?;
+
+        let client = Client::new_with_base_url(api_url());
+
+        let keypair = wallet.decrypt(password.as_bytes())?;
+        let fee_config = get_txn_fees(&client, &opts.vars_file)?;
+        let nonce = get_nonce(
+            &client,
+            &keypair.pubkey_bin(),
+            NonceKind::Hnt,
+            self.nonce,
+            self.commit.commit,
+        )?;
+
+        let mut txn = self.mk_txn(
+            &client,
+            keypair.pubkey_bin(),
+            nonce.nonce,
+            &fee_config,
+            request.as_ref(),
+        )?;
+        txn.signature = sign_txn(&txn, &keypair)?;
+        let envelope = txn.in_envelope();
+        let cost = self.commit.validate(&client, &txn)?;
+        let status = self
+            .commit
+            .submit_with(&client, &envelope, Some(nonce), &details)?;
+
+        print_txn(
+            &txn,
+            &envelope,
+            &cost,
+            &status,
+            &request,
+            &details,
+            opts.format,
+        )?;
+
+        self.commit.maybe_wait(&status, opts.format)
+    }
+
+    fn run_batch(&self, path: &PathBuf, opts: Opts) -> Result {
+        let payments = batch::read_payments(path)?;
+
+        let password = get_password(false)?;
+        let wallet = load_wallet(opts.files)?;
+
+        let client = Client::new_with_base_url(api_url());
+
+        let keypair = wallet.decrypt(password.as_bytes())?;
+        let vars = get_vars(&client, &opts.vars_file)?;
+        let max_payments = vars
+            .get("max_payments")
+            .and_then(|v| v.as_u64())
+            .filter(|v| *v > 0)
+            .map_or(DEFAULT_MAX_PAYMENTS, |v| v as usize);
+        let fee_config = txn_fees(vars)?;
+
+        let mut first_nonce = Some(get_nonce(
+            &client,
+            &keypair.pubkey_bin(),
+            NonceKind::Hnt,
+            self.nonce,
+            self.commit.commit,
+        )?);
+        let base_nonce = first_nonce.as_ref().map_or(0, |nonce| nonce.nonce);
+        // All payments are validated as a single transaction with the
+        // combined fee before any of the transactions is submitted
+        let mut total_fee = 0;
+        for (index, chunk) in payments.chunks(max_payments).enumerate() {
+            let chunk_payments = chunk.iter().map(|p| p.to_payment()).collect();
+            let nonce = base_nonce + index as u64;
+            total_fee += self
+                .mk_payment_txn(chunk_payments, keypair.pubkey_bin(), nonce, &fee_config)?
+                .fee;
+        }
+        let combined = BlockchainTxnPaymentV2 {
+            payer: keypair.pubkey_bin().into(),
+            payments: payments.iter().map(|p| p.to_payment()).collect(),
+            nonce: base_nonce,
+            fee: total_fee,
+            signature: vec![],
+        };
+        let cost = self.commit.validate(&client, &combined)?;
+        self.commit.confirm(&combined.in_envelope())?;
+        let mut txns = vec![];
+        let mut failure = None;
+        for (index, chunk) in payments.chunks(max_payments).enumerate() {
+            // Reserve each following nonce only when its transaction
+            // is about to be submitted
+            let nonce = match first_nonce.take() {
+                Some(nonce) => nonce,
+                None => get_nonce(
+                    &client,
+                    &keypair.pubkey_bin(),
+                    NonceKind::Hnt,
+                    Some(base_nonce + index as u64),
+                    self.commit.commit,
+                )?,
+            };
+            let chunk_payments = chunk.iter().map(|p| p.to_payment()).collect();
+            let mut txn = self.mk_payment_txn(
+                chunk_payments,
+                keypair.pubkey_bin(),
+                nonce.nonce,
+                &fee_config,
+            )?;
+            txn.signature = sign_txn(&txn, &keypair)?;
+            let envelope = txn.in_envelope();
+            let status = if self.commit.commit {
+                match submit_txn(&client, &envelope, Some(nonce)) {
+                    Ok(status) => Some(status),
+                    Err(err) => {
+                        failure = Some(err);
+                        break;
+                    }
+                }
+            } else {
+                update_journal(&envelope, JournalStatus::Preview, None);
+                None
+            };
+            txns.push((txn, envelope, status));
+        }
+
+        batch::print_batch(&payments, max_payments, &txns, &cost, opts.format)?;
+        if let Some(err) = failure {
+            return Err(format!(
+                "Failed to submit payment transaction {}, later transactions were not submitted: {}",
+                txns.len() + 1,
+                err
+            )
+            .into());
+        }
+        for (_, _, status) in &txns {
+            self.commit.maybe_wait(status, opts.format)?;
+        }
+        Ok(())
+    }
+
+    /// Construct the unsigned payment transaction for the given payer.
+    /// If one of the payees is to receive the maximum amount, the
+    /// balance of the payer is fetched and whatever is left after the
+    /// other payments and the transaction fee is sent to that payee.
+    /// The given payment request is expected to have been checked with
+    /// `payment_request`.
+    pub(crate) fn mk_txn(
+        &self,
+        client: &Client,
+        payer: PubKeyBin,
+        nonce: u64,
+        fee_config: &TxnFeeConfig,
+        request: Option<&PaymentRequest>,
+    ) -> Result<BlockchainTxnPaymentV2> {
+        if self.from_file.is_some() {
+            return Err("Payments from a file can not be built as a single transaction".into());
+        }
+        let mut payments = Vec::with_capacity(self.payees.len() + 1);
+        let mut max_index = None;
+        let mut oracle_price = None;
+        let request_payee = match request {
+            Some(request) => Some(Payee {
+                address: request.address.to_b58()?,
+                amount: PayeeAmount::Amount(request.amount.ok_or("Payment request has no amount")?),
+            }),
+            None => None,
+        };
+        let payees = self
+            .payees
+            .iter()
+            .chain(request_payee.iter())
+            .map(|p| (p.address.as_str(), &p.amount))
+            .chain(self.sweep.iter().map(|a| (a.as_str(), &PayeeAmount::Max)));
+        for (address, amount) in payees {
+            let payee = PubKeyBin::from_b58(address)?.into();
+            match amount {
+                PayeeAmount::Amount(amount) => payments.push(Payment {
+                    payee,
+                    amount: amount.to_bones(|| cached_oracle_price(&mut oracle_price))?,
+                }),
+                PayeeAmount::Max if max_index.is_some() => {
+                    return Err("Only one payee can receive the maximum amount".into())
+                }
+                PayeeAmount::Max => {
+                    max_index = Some(payments.len());
+                    payments.push(Payment { payee, amount: 0 });
+                }
+            }
+        }
+        let max_index = match max_index {
+            Some(index) => index,
+            None => return self.mk_payment_txn(payments, payer, nonce, fee_config),
+        };
+
+        let account = client.get_account(&payer.to_b58()?)?;
+        let fixed = total_amount(payments.iter().map(|p| p.amount))?;
+        let available = account
+            .balance
+            .checked_sub(fixed)
+            .ok_or("Insufficient balance for the given payments")?;
+        let mut fee_bones = 0;
+        // The fee depends on the encoded size of the amount, so it is
+        // recalculated until it no longer changes
+        for _ in 0..MAX_SWEEP_ITERATIONS {
+            payments[max_index].amount = available
+                .checked_sub(fee_bones)
+                .filter(|amount| *amount > 0)
+                .ok_or("Insufficient balance to cover the transaction fee")?;
+            let txn = self.mk_payment_txn(payments.clone(), payer, nonce, fee_config)?;
+            // Fees are paid in DC, and HNT is burned for them only if
+            // the DC balance is insufficient
+            let needed = if account.dc_balance >= txn.fee {
+                0
+            } else {
+                dc_to_bones(txn.fee, cached_oracle_price(&mut oracle_price)?)?
+            };
+            if needed == fee_bones {
+                return Ok(txn);
+            }
+            fee_bones = needed;
+        }
+        Err("Unable to determine a stable fee for the remaining balance".into())
+    }
+
+    /// Read and check the payment request to pay, if one was given
+    pub(crate) fn payment_request(&self) -> Result<Option<PaymentRequest>> {
+        let request = match &self.request {
+            Some(request) => PaymentRequest::read(request)?,
+            None => return Ok(None),
+        };
+        if request.request_type != RequestType::Payment {
+            return Err(format!(
+                "Request is a {} request, not a payment",
+                request.request_type
+            )
+            .into());
+        }
+        if request.memo.is_some() {
+            return Err("Requests with a memo can not be paid, payments have no memo".into());
+        }
+        // An unsigned request is shown as unverified, but a request
+        // with a bad signature has been tampered with
+        if let Some(expected) = &self.expect_requester {
+            request.check_requester(expected)?;
+        } else if request.signature.is_some() {
+            request.verify()?;
+        }
+        request.check_expiry()?;
+        Ok(Some(request))
+    }
+
+    fn mk_payment_txn(
+        &self,
+        payments: Vec<Payment>,
+        payer: PubKeyBin,
+        nonce: u64,
+        fee_config: &TxnFeeConfig,
+    ) -> Result<BlockchainTxnPaymentV2> {
+        let mut txn = BlockchainTxnPaymentV2 {
+            fee: 0,
+            payments,
+            payer: payer.into(),
+            nonce,
+            signature: Vec::new(),
+        };
+
+        txn.fee = if let Some(fee) = self.fee {
+            fee
+        } else {
+            txn.txn_fee(fee_config)?
+        };
+        Ok(txn)
+    }
+}
+
+fn print_txn(
+    txn: &BlockchainTxnPaymentV2,
+    envelope: &BlockchainTxn,
+    cost: &FeeCost,
+    status: &Option<PendingTxnStatus>,
+    request: &Option<PaymentRequest>,
+    details: &[(&'static str, String)],
+    format: OutputFormat,
+) -> Result {
+    match format {
+        OutputFormat::Table => {
+            if !details.is_empty() {
+                let mut table = Table::new();
+                table.add_row(row!["Key", "Value"]);
+                for (key, value) in details {
+                    table.add_row(row![key, value]);
+                }
+                print_table(&table)?;
+            }
+
+            let mut table = Table::new();
+            table.add_row(row!["Payee", "Amount"]);
+            for payment in txn.payments.clone() {
+                table.add_row(row![
+                    PubKeyBin::from_vec(&payment.payee).to_b58().unwrap(),
+                    Hnt::from_bones(payment.amount)
+                ]);
+            }
+            print_table(&table)?;
+
+            ptable!(
+                ["Key", "Value"],
+                ["Fee (DC)", cost.dc],
+                ["Fee (HNT)", cost.hnt],
+                ["Implicit Burn", cost.implicit_burn],
+                ["Nonce", txn.nonce],
+                ["Hash", status_str(envelope, status)?]
+            );
+
+            print_footer(status)
+        }
+        OutputFormat::Json => {
+            let mut payments = Vec::with_capacity(txn.payments.len());
+            for payment in txn.payments.clone() {
+                payments.push(json!({
+                    "payee": PubKeyBin::from_vec(&payment.payee).to_b58().unwrap(),
+                    "amount": Hnt::from_bones(payment.amount),
+                }))
+            }
+            let mut table = json!({
+                "payments": payments,
+                "fee": cost.dc,
+                "fee_hnt": cost.hnt,
+                "implicit_burn": cost.implicit_burn,
+                "nonce": txn.nonce,
+                "hash": status_json(envelope, status)?,
+                "txn": envelope.to_b64()?,
+            });
+            if let Some(request) = request {
+                table["request"] = request.to_json()?;
+            }
+            print_json(&table)
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Payee {
+    address: String,
+    amount: PayeeAmount,
+}
+
+/// The amount to send to a payee, in any of the units an amount can be
+/// given in. "max" sends whatever is left of the balance after all
+/// other payments and the transaction fee.
+#[derive(Debug)]
+pub enum PayeeAmount {
+    Amount(Amount),
+    Max,
+}
+
+impl FromStr for PayeeAmount {
+    type Err = Box<dyn std::error::Error>;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("max") {
+            Ok(PayeeAmount::Max)
+        } else {
+            Ok(PayeeAmount::Amount(s.parse()?))
+        }
+    }
+}
+
+impl FromStr for Payee {
+    type Err = Box<dyn std::error::Error>;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let pos = s
+            .find('=')
+            .ok_or_else(|| format!("invalid KEY=value: missing `=`  in `{}`", s))?;
+        Ok(Payee {
+            address: s[..pos].to_string(),
+            amount: s[pos + 1..].parse()?,
+        })
+    }
+}
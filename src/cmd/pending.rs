@@ -0,0 +1,372 @@
+use crate::{
+    api::{self, PendingTxnState},
+    cmd::{
+        api_url, data_dir, get_password, get_txn_fees, load_wallet, print_json, print_table,
+        sign_txn, CommitOpts, Opts, OutputFormat,
+    },
+    journal::{Journal, JournalStatus},
+    keypair::{Keypair, PubKeyBin},
+    result::Result,
+    traits::{TxnEnvelope, TxnFee, TxnFeeConfig, TxnHash, B58, B64},
+};
+use helium_api::{BlockchainTxn, Client, Txn};
+use prettytable::{format, Table};
+use serde_json::json;
+use std::fmt;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+/// List the in-flight transactions of this wallet, as reported by the
+/// API and recorded in the local journal, and detect gaps in their
+/// nonces that keep later transactions from clearing. With --repair
+/// dropped transactions are rebroadcast and failed ones are re-signed
+/// with the current fee to fill the gaps. Dropped transactions whose fee
+/// is no longer current are re-signed as well. Repairs are validated
+/// like new transactions and nothing is submitted unless the '--commit'
+/// option is given.
+pub struct Cmd {
+    /// Rebroadcast or replace the transactions for missing nonces
+    #[structopt(long)]
+    repair: bool,
+
+    #[structopt(flatten)]
+    commit: CommitOpts,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum InFlightState {
+    /// Known to the API as received or pending
+    Pending,
+    /// Failed according to the API or the journal
+    Failed,
+    /// Submitted according to the journal but unknown to the API
+    Dropped,
+    /// Signed with `txn sign` but never submitted
+    Unsent,
+}
+
+impl fmt::Display for InFlightState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Self::Pending => "pending",
+            Self::Failed => "failed",
+            Self::Dropped => "dropped",
+            Self::Unsent => "unsent",
+        };
+        f.write_str(s)
+    }
+}
+
+/// A transaction using one of the account nonces that have not cleared
+/// yet
+struct InFlight {
+    nonce: u64,
+    hash: String,
+    txn_type: String,
+    state: InFlightState,
+    /// The signed transaction, if it was recorded in the journal
+    envelope: Option<BlockchainTxn>,
+}
+
+/// The action taken to fill a nonce gap
+struct Repair {
+    nonce: u64,
+    action: &'static str,
+    hash: String,
+    submitted: bool,
+}
+
+/// The account nonce used by a transaction paid for by the given
+/// wallet
+fn account_nonce(envelope: &BlockchainTxn, wallet: &PubKeyBin) -> Option<u64> {
+    let (payer, nonce) = match &envelope.txn {
+        Some(Txn::Payment(t)) => (&t.payer, t.nonce),
+        Some(Txn::PaymentV2(t)) => (&t.payer, t.nonce),
+        Some(Txn::CreateHtlc(t)) => (&t.payer, t.nonce),
+        Some(Txn::TokenBurn(t)) => (&t.payer, t.nonce),
+        Some(Txn::TransferHotspot(t)) => (&t.buyer, t.buyer_nonce),
+        _ => return None,
+    };
+    if payer.as_slice() == wallet.0.as_ref() {
+        Some(nonce)
+    } else {
+        None
+    }
+}
+
+/// Whether the fee of the given transaction is the fee required by the
+/// given fee configuration. Transactions that can not be re-signed are
+/// taken to have a current fee.
+fn has_current_fee(envelope: &BlockchainTxn, fee_config: &TxnFeeConfig) -> Result<bool> {
+    macro_rules! current {
+        ($txn:expr) => {
+            Ok($txn.fee == $txn.txn_fee(fee_config)?)
+        };
+    }
+    match &envelope.txn {
+        Some(Txn::Payment(t)) => current!(t),
+        Some(Txn::PaymentV2(t)) => current!(t),
+        Some(Txn::CreateHtlc(t)) => current!(t),
+        Some(Txn::TokenBurn(t)) => current!(t),
+        _ => Ok(true),
+    }
+}
+
+/// Re-sign the given transaction with the fee of the given fee
+/// configuration
+fn resign(
+    envelope: &BlockchainTxn,
+    fee_config: &TxnFeeConfig,
+    keypair: &Keypair,
+) -> Result<BlockchainTxn> {
+    macro_rules! resign {
+        ($txn:expr) => {{
+            let mut txn = $txn.clone();
+            txn.fee = txn.txn_fee(fee_config)?;
+            txn.signature = sign_txn(&txn, keypair)?;
+            Ok(txn.in_envelope())
+        }};
+    }
+    match &envelope.txn {
+        Some(Txn::Payment(t)) => resign!(t),
+        Some(Txn::PaymentV2(t)) => resign!(t),
+        Some(Txn::CreateHtlc(t)) => resign!(t),
+        Some(Txn::TokenBurn(t)) => resign!(t),
+        _ => Err("Only payment, burn and htlc transactions can be re-signed".into()),
+    }
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        let wallet = load_wallet(opts.files.clone())?.pubkey_bin;
+        let client = Client::new_with_base_url(api_url());
+        let api_client = api::Client::new_with_base_url(api_url());
+        let account = client.get_account(&wallet.to_b58()?)?;
+
+        // Collect the transactions for every nonce past the last
+        // cleared one, from the API first and then from the journal
+        let mut txns: Vec<InFlight> = vec![];
+        for pending in api_client.get_account_pending_txns(&wallet.to_b58()?)? {
+            let nonce = match pending.nonce() {
+                Some(nonce) if nonce > account.nonce => nonce,
+                _ => continue,
+            };
+            let state = match pending.status {
+                PendingTxnState::Received | PendingTxnState::Pending => InFlightState::Pending,
+                PendingTxnState::Failed => InFlightState::Failed,
+                _ => continue,
+            };
+            txns.push(InFlight {
+                nonce,
+                hash: pending.hash,
+                txn_type: pending.txn_type.unwrap_or_else(|| "unknown".to_string()),
+                state,
+                envelope: None,
+            });
+        }
+        let journal = Journal::open(&data_dir()?, &wallet)?;
+        for entry in journal.latest()? {
+            // Transactions that were only built or previewed were
+            // never meant to be submitted
+            if matches!(entry.status, JournalStatus::Built | JournalStatus::Preview) {
+                continue;
+            }
+            let envelope = entry.to_txn()?;
+            let nonce = match account_nonce(&envelope, &wallet) {
+                Some(nonce) if nonce > account.nonce => nonce,
+                _ => continue,
+            };
+            if let Some(txn) = txns.iter_mut().find(|txn| txn.hash == entry.hash) {
+                txn.envelope = Some(envelope);
+                continue;
+            }
+            let state = match (entry.status, api_client.get_pending_txn(&entry.hash)?) {
+                (_, Some(pending)) if pending.status == PendingTxnState::Cleared => continue,
+                (_, Some(pending)) if pending.status == PendingTxnState::Failed => {
+                    InFlightState::Failed
+                }
+                (_, Some(_)) => InFlightState::Pending,
+                (JournalStatus::Failed, None) => InFlightState::Failed,
+                (JournalStatus::Signed, None) => InFlightState::Unsent,
+                _ => InFlightState::Dropped,
+            };
+            txns.push(InFlight {
+                nonce,
+                hash: entry.hash,
+                txn_type: entry.txn_type,
+                state,
+                envelope: Some(envelope),
+            });
+        }
+        txns.sort_by_key(|txn| txn.nonce);
+
+        // A gap is a nonce without a pending transaction below the
+        // highest pending nonce. Transactions past a gap never clear.
+        let pending_nonces: Vec<u64> = txns
+            .iter()
+            .filter(|txn| txn.state == InFlightState::Pending)
+            .map(|txn| txn.nonce)
+            .collect();
+        let gaps: Vec<u64> = match pending_nonces.iter().max() {
+            Some(max) => (account.nonce + 1..*max)
+                .filter(|nonce| !pending_nonces.contains(nonce))
+                .collect(),
+            None => vec![],
+        };
+
+        let repairs = if self.repair {
+            // Dropped transactions are rebroadcast even when no later
+            // transaction is waiting on them yet
+            let mut nonces = gaps.clone();
+            for txn in &txns {
+                if txn.state == InFlightState::Dropped
+                    && !pending_nonces.contains(&txn.nonce)
+                    && !nonces.contains(&txn.nonce)
+                {
+                    nonces.push(txn.nonce);
+                }
+            }
+            nonces.sort_unstable();
+            self.repair(&nonces, &txns, &client, &opts)?
+        } else {
+            vec![]
+        };
+        print_pending(account.nonce, &txns, &gaps, &repairs, opts.format)
+    }
+
+    fn repair(
+        &self,
+        nonces: &[u64],
+        txns: &[InFlight],
+        client: &Client,
+        opts: &Opts,
+    ) -> Result<Vec<Repair>> {
+        if nonces.is_empty() {
+            return Ok(vec![]);
+        }
+        let fee_config = get_txn_fees(client, &opts.vars_file)?;
+        let mut keypair = None;
+        let mut resign = |envelope: &BlockchainTxn| -> Result<BlockchainTxn> {
+            if keypair.is_none() {
+                let password = get_password(false)?;
+                let wallet = load_wallet(opts.files.clone())?;
+                keypair = Some(wallet.decrypt(password.as_bytes())?);
+            }
+            resign(envelope, &fee_config, keypair.as_ref().unwrap())
+        };
+        let mut repairs = vec![];
+        for nonce in nonces {
+            let find = |state| -> Vec<&BlockchainTxn> {
+                txns.iter()
+                    .filter(|txn| txn.nonce == *nonce && txn.state == state)
+                    .filter_map(|txn| txn.envelope.as_ref())
+                    .collect()
+            };
+            let mut candidates = find(InFlightState::Dropped);
+            if candidates.is_empty() {
+                candidates = find(InFlightState::Unsent);
+            }
+            let rebroadcast = !candidates.is_empty();
+            if !rebroadcast {
+                candidates = find(InFlightState::Failed);
+            }
+            // Several transactions may have been signed for the same
+            // nonce, in which case it is unknown which one to send
+            let envelope = match candidates.as_slice() {
+                [envelope] => *envelope,
+                [] => {
+                    eprintln!(
+                        "warning: no local transaction for nonce {}, unable to repair it",
+                        nonce
+                    );
+                    continue;
+                }
+                _ => {
+                    eprintln!(
+                        "warning: {} local transactions for nonce {}, resubmit the right one with txn resubmit",
+                        candidates.len(),
+                        nonce
+                    );
+                    continue;
+                }
+            };
+            let (action, envelope) = if rebroadcast && has_current_fee(envelope, &fee_config)? {
+                ("rebroadcast", envelope.clone())
+            } else {
+                ("replace", resign(envelope)?)
+            };
+            // The nonces before this one are pending or repaired before
+            // it
+            self.commit.validate_after(client, &envelope, nonce - 1)?;
+            let status = self.commit.submit(client, &envelope, None)?;
+            repairs.push(Repair {
+                nonce: *nonce,
+                action,
+                hash: match &status {
+                    Some(status) => status.hash.clone(),
+                    None => envelope.txn_hash()?.to_b64_url()?,
+                },
+                submitted: status.is_some(),
+            });
+        }
+        Ok(repairs)
+    }
+}
+
+fn print_pending(
+    account_nonce: u64,
+    txns: &[InFlight],
+    gaps: &[u64],
+    repairs: &[Repair],
+    format: OutputFormat,
+) -> Result {
+    match format {
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+            table.set_titles(row!["Nonce", "Hash", "Type", "Status"]);
+            for txn in txns {
+                table.add_row(row![txn.nonce, txn.hash, txn.txn_type, txn.state]);
+            }
+            print_table(&table)?;
+            println!("Last cleared nonce: {}", account_nonce);
+            for gap in gaps {
+                println!("Nonce gap: {}", gap);
+            }
+            if !repairs.is_empty() {
+                let mut table = Table::new();
+                table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+                table.set_titles(row!["Nonce", "Action", "Hash", "Submitted"]);
+                for repair in repairs {
+                    table.add_row(row![
+                        repair.nonce,
+                        repair.action,
+                        repair.hash,
+                        repair.submitted
+                    ]);
+                }
+                print_table(&table)?;
+            }
+            Ok(())
+        }
+        OutputFormat::Json => {
+            let table = json!({
+                "nonce": account_nonce,
+                "txns": txns.iter().map(|txn| json!({
+                    "nonce": txn.nonce,
+                    "hash": txn.hash,
+                    "type": txn.txn_type,
+                    "status": txn.state.to_string(),
+                })).collect::<Vec<_>>(),
+                "gaps": gaps,
+                "repairs": repairs.iter().map(|repair| json!({
+                    "nonce": repair.nonce,
+                    "action": repair.action,
+                    "hash": repair.hash,
+                    "submitted": repair.submitted,
+                })).collect::<Vec<_>>(),
+            });
+            print_json(&table)
+        }
+    }
+}
@@ -1,9 +1,15 @@
 use crate::{
-    cmd::{load_wallet, print_json, Opts, OutputFormat},
+    amount::Amount,
+    cmd::{
+        cached_oracle_price, get_password, load_wallet, print_json, print_table, Opts,
+        OutputFormat, QrOpts,
+    },
+    memo::{Memo, MemoFormat},
+    payment_request::{now, PaymentRequest, RequestType},
     result::Result,
+    wallet::Wallet,
 };
-use helium_api::Hnt;
-use qr2term::print_qr;
+use prettytable::{format, Table};
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
@@ -17,21 +23,62 @@ pub enum Cmd {
 /// Construct a payment request in a QR code with optional amount
 pub struct Payment {
     #[structopt(long)]
-    /// Amount of HNT to request
-    amount: Option<Hnt>,
+    /// Amount to request, in HNT unless a unit of bones, dc or usd is
+    /// given. DC and USD amounts are converted at the current oracle price
+    amount: Option<Amount>,
+
+    #[structopt(flatten)]
+    request: RequestOpts,
 }
 
 #[derive(Debug, StructOpt)]
 /// Construct a token burn request in a QR code with optional amount and memo
 /// fields.
 pub struct Burn {
-    /// Memo field to include. Provide as a base64 encoded string
+    /// Memo field to include, in the format given by --memo-format
     #[structopt(long)]
     memo: Option<String>,
 
-    /// Amount of HNT to burn to DC
+    /// Format of the memo: base64 encoded, a decimal or hexadecimal
+    /// number, or up to 8 ASCII characters
+    #[structopt(long,
+                possible_values = &["base64", "decimal", "hex", "ascii"],
+                case_insensitive = true,
+                default_value = "base64")]
+    memo_format: MemoFormat,
+
+    /// Amount to burn to DC, in HNT unless a unit of bones, dc or usd is
+    /// given. DC and USD amounts are converted at the current oracle price
     #[structopt(long)]
-    amount: Option<Hnt>,
+    amount: Option<Amount>,
+
+    #[structopt(flatten)]
+    request: RequestOpts,
+}
+
+// Options shared by all requests
+#[derive(Debug, StructOpt)]
+pub struct RequestOpts {
+    /// A label describing the request, like an invoice number
+    #[structopt(long)]
+    label: Option<String>,
+
+    /// Number of minutes after which the request expires
+    #[structopt(long)]
+    expires_in: Option<u64>,
+
+    /// Sign the request with the wallet so payers can verify it was
+    /// created by the owner of the requested address
+    #[structopt(long)]
+    sign: bool,
+
+    /// Encode the request in the QR code as a "helium:" URI instead of
+    /// as JSON
+    #[structopt(long)]
+    uri: bool,
+
+    #[structopt(flatten)]
+    qr: QrOpts,
 }
 
 impl Cmd {
@@ -47,14 +94,10 @@ impl Payment {
     pub fn run(&self, opts: Opts) -> Result {
         let wallet = load_wallet(opts.files)?;
 
-        let mut request = json!({
-            "type": "payment",
-            "address": wallet.address()?,
-        });
-        if self.amount.is_some() {
-            request["amount"] = self.amount.unwrap().to_string().into();
-        }
-        print_request(&request, opts.format)
+        let mut request = PaymentRequest::new(RequestType::Payment, wallet.pubkey_bin);
+        request.amount = self.amount.map(hnt_amount).transpose()?;
+        self.request.apply(&mut request, &wallet)?;
+        self.request.print(&request, opts.format)
     }
 }
 
@@ -62,27 +105,96 @@ impl Burn {
     pub fn run(&self, opts: Opts) -> Result {
         let wallet = load_wallet(opts.files)?;
 
-        let mut request = json!({
-            "type": "dc_burn",
-            "address": wallet.address()?,
-        });
-        if self.amount.is_some() {
-            request["amount"] = self.amount.unwrap().to_string().into();
+        let mut request = PaymentRequest::new(RequestType::Burn, wallet.pubkey_bin);
+        request.amount = self.amount.map(hnt_amount).transpose()?;
+        request.memo = match &self.memo {
+            Some(memo) => Some(Memo::parse(memo, self.memo_format)?),
+            None => None,
+        };
+        self.request.apply(&mut request, &wallet)?;
+        self.request.print(&request, opts.format)?;
+        match (request.memo, opts.format) {
+            (Some(memo), OutputFormat::Table) => print_memo(&memo),
+            _ => Ok(()),
         }
-        if self.memo.is_some() {
-            let value = self.memo.as_ref().unwrap();
-            request["memo"] = value.clone().into();
+    }
+}
+
+impl RequestOpts {
+    /// Set the label and expiry of the given request, and sign it with
+    /// the given wallet if requested.
+    pub(crate) fn apply(&self, request: &mut PaymentRequest, wallet: &Wallet) -> Result {
+        request.label = self.label.clone();
+        if let Some(minutes) = self.expires_in {
+            request.expires = Some(now()? + minutes * 60);
+        }
+        if self.sign {
+            let password = get_password(false)?;
+            let keypair = wallet.decrypt(password.as_bytes())?;
+            request.sign(&keypair)?;
         }
-        print_request(&request, opts.format)
+        Ok(())
+    }
+
+    fn print(&self, request: &PaymentRequest, format: OutputFormat) -> Result {
+        print_request(request, self.uri, &self.qr, format)
+    }
+}
+
+/// The contents of the QR code for the given request: its URI if `uri`
+/// is set, and its JSON otherwise.
+pub(crate) fn qr_payload(request: &PaymentRequest, uri: bool) -> Result<String> {
+    if uri {
+        request.to_uri()
+    } else {
+        Ok(serde_json::to_string(&request.to_json()?)?)
     }
 }
 
-fn print_request(request: &serde_json::Value, format: OutputFormat) -> Result {
+/// Print the given request as a QR code with its details, or as JSON
+/// including its URI. A QR code file is written in either format if
+/// one was requested.
+pub(crate) fn print_request(
+    request: &PaymentRequest,
+    uri: bool,
+    qr: &QrOpts,
+    format: OutputFormat,
+) -> Result {
     match format {
-        OutputFormat::Json => print_json(request),
+        OutputFormat::Json => {
+            qr.write(&qr_payload(request, uri)?)?;
+            let mut json = request.to_json()?;
+            json["uri"] = request.to_uri()?.into();
+            print_json(&json)
+        }
         OutputFormat::Table => {
-            print_qr(&serde_json::to_string(&request)?)?;
-            Ok(())
+            qr.output(&qr_payload(request, uri)?)?;
+            let mut table = Table::new();
+            table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+            table.set_titles(row!["Key", "Value"]);
+            for (key, value) in request.details(None)? {
+                table.add_row(row![key, value]);
+            }
+            print_table(&table)
         }
     }
 }
+
+/// The requested amount in HNT, which is what wallets scanning the
+/// request expect. DC and USD amounts are converted at the current
+/// oracle price.
+fn hnt_amount(amount: Amount) -> Result<Amount> {
+    let bones = amount.to_bones(|| cached_oracle_price(&mut None))?;
+    Ok(Amount::Bones(bones))
+}
+
+fn print_memo(memo: &Memo) -> Result {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+    table.set_titles(row!["Memo", "Value"]);
+    table.add_row(row!["Base64", memo.to_b64()?]);
+    table.add_row(row!["Decimal", memo.0]);
+    table.add_row(row!["Hex", memo.to_hex()]);
+    table.add_row(row!["ASCII", memo.to_ascii().unwrap_or_default()]);
+    print_table(&table)
+}
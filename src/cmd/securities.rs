@@ -1,11 +1,12 @@
 use crate::{
     cmd::{
-        api_url, get_password, get_txn_fees, load_wallet, print_footer, print_json, status_json,
-        status_str, Opts, OutputFormat,
+        api_url, get_nonce, get_password, get_txn_fees, load_wallet, print_footer, print_json,
+        sign_txn, status_json, status_str, CommitOpts, FeeCost, Opts, OutputFormat,
     },
     keypair::PubKeyBin,
+    nonce::NonceKind,
     result::Result,
-    traits::{Sign, TxnEnvelope, TxnFee, B58, B64},
+    traits::{TxnEnvelope, TxnFee, TxnFeeConfig, B58, B64},
 };
 use helium_api::{BlockchainTxn, BlockchainTxnSecurityExchangeV1, Client, Hst, PendingTxnStatus};
 use serde_json::json;
@@ -26,9 +27,12 @@ pub struct Transfer {
     /// The number of security tokens to transfer
     amount: Hst,
 
-    /// Commit the transfter to the API
+    /// Manually set the security nonce to use for the transaction
     #[structopt(long)]
-    commit: bool,
+    pub(crate) nonce: Option<u64>,
+
+    #[structopt(flatten)]
+    commit: CommitOpts,
 }
 
 impl Cmd {
@@ -47,32 +51,52 @@ impl Transfer {
         let client = Client::new_with_base_url(api_url());
 
         let keypair = wallet.decrypt(password.as_bytes())?;
-        let account = client.get_account(&keypair.public.to_b58()?)?;
+        let fee_config = get_txn_fees(&client, &opts.vars_file)?;
+        let nonce = get_nonce(
+            &client,
+            &keypair.pubkey_bin(),
+            NonceKind::Security,
+            self.nonce,
+            self.commit.commit,
+        )?;
+
+        let mut txn = self.mk_txn(keypair.pubkey_bin(), nonce.nonce, &fee_config)?;
+        txn.signature = sign_txn(&txn, &keypair)?;
+        let envelope = txn.in_envelope();
+        let cost = self.commit.validate(&client, &txn)?;
+        let status = self.commit.submit(&client, &envelope, Some(nonce))?;
 
+        print_txn(&txn, &envelope, &cost, &status, opts.format)?;
+
+        self.commit.maybe_wait(&status, opts.format)
+    }
+
+    /// Construct the unsigned security exchange transaction for the
+    /// given payer. Note that the nonce is the security nonce of the
+    /// payer.
+    pub(crate) fn mk_txn(
+        &self,
+        payer: PubKeyBin,
+        nonce: u64,
+        fee_config: &TxnFeeConfig,
+    ) -> Result<BlockchainTxnSecurityExchangeV1> {
         let mut txn = BlockchainTxnSecurityExchangeV1 {
-            payer: keypair.pubkey_bin().into(),
+            payer: payer.into(),
             payee: PubKeyBin::from_b58(&self.payee)?.into(),
             amount: self.amount.to_bones(),
-            nonce: account.speculative_sec_nonce + 1,
+            nonce,
             fee: 0,
             signature: vec![],
         };
-        txn.fee = txn.txn_fee(&get_txn_fees(&client)?)?;
-        txn.signature = txn.sign(&keypair)?;
-        let envelope = txn.in_envelope();
-        let status = if self.commit {
-            Some(client.submit_txn(&envelope)?)
-        } else {
-            None
-        };
-
-        print_txn(&txn, &envelope, &status, opts.format)
+        txn.fee = txn.txn_fee(fee_config)?;
+        Ok(txn)
     }
 }
 
 fn print_txn(
     txn: &BlockchainTxnSecurityExchangeV1,
     envelope: &BlockchainTxn,
+    cost: &FeeCost,
     status: &Option<PendingTxnStatus>,
     format: OutputFormat,
 ) -> Result {
@@ -87,8 +111,11 @@ fn print_txn(
             );
             ptable!(
                 ["Key", "Value"],
+                ["Fee (DC)", cost.dc],
+                ["Fee (HNT)", cost.hnt],
+                ["Implicit Burn", cost.implicit_burn],
                 ["Nonce", txn.nonce],
-                ["Hash", status_str(status)]
+                ["Hash", status_str(envelope, status)?]
             );
 
             print_footer(status)
@@ -100,8 +127,11 @@ fn print_txn(
             });
             let table = json!({
                 "transfer": transfer,
+                "fee": cost.dc,
+                "fee_hnt": cost.hnt,
+                "implicit_burn": cost.implicit_burn,
                 "nonce": txn.nonce,
-                "hash": status_json(status),
+                "hash": status_json(envelope, status)?,
                 "txn": envelope.to_b64()?,
             });
             print_json(&table)
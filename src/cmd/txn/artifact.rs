@@ -0,0 +1,115 @@
+use crate::{
+    journal::txn_nonce,
+    result::Result,
+    traits::{TxnFeeConfig, TxnVerify, B64},
+};
+use helium_api::BlockchainTxn;
+use serde::{Deserialize, Serialize};
+use std::{fs::File, path::Path};
+
+/// A transaction container that can be moved between machines. It
+/// carries the (partially) signed transaction along with the nonce and
+/// fee configuration it was built with and the signers it still
+/// requires, so it can be signed without access to the network.
+///
+/// A plain `{"txn": ...}` file, as used for multisig transactions, is
+/// a valid artifact.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Artifact {
+    txn: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    nonce: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fee_config: Option<TxnFeeConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    signers: Vec<ArtifactSigner>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ArtifactSigner {
+    pub role: String,
+    pub address: String,
+}
+
+impl Artifact {
+    pub fn from_txn(txn: &BlockchainTxn) -> Result<Self> {
+        Ok(Self {
+            txn: txn.to_b64()?,
+            nonce: None,
+            fee_config: None,
+            signers: vec![],
+        })
+    }
+
+    /// Construct an artifact for an unsigned transaction, recording
+    /// the addresses that are required to sign it.
+    pub fn build(
+        txn: &BlockchainTxn,
+        nonce: Option<u64>,
+        fee_config: TxnFeeConfig,
+    ) -> Result<Self> {
+        let signers = txn
+            .verify_signatures()?
+            .into_iter()
+            .filter_map(|check| {
+                check.signer.map(|signer| ArtifactSigner {
+                    role: check.role.to_string(),
+                    address: signer.to_string(),
+                })
+            })
+            .collect();
+        Ok(Self {
+            txn: txn.to_b64()?,
+            nonce,
+            fee_config: Some(fee_config),
+            signers,
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let file = File::open(path)?;
+        let artifact: Artifact = serde_json::from_reader(&file)?;
+        Ok(artifact)
+    }
+
+    pub fn to_txn(&self) -> Result<BlockchainTxn> {
+        BlockchainTxn::from_b64(&self.txn)
+    }
+
+    pub fn set_txn(&mut self, txn: &BlockchainTxn) -> Result {
+        self.txn = txn.to_b64()?;
+        Ok(())
+    }
+
+    /// Check that the transaction still uses the nonce the artifact was
+    /// built with.
+    pub fn check_nonce(&self, txn: &BlockchainTxn) -> Result {
+        match (self.nonce, txn_nonce(txn)) {
+            (Some(expected), Some(nonce)) if expected != nonce => Err(format!(
+                "Transaction nonce {} does not match artifact nonce {}",
+                nonce, expected
+            )
+            .into()),
+            (Some(expected), None) => Err(format!(
+                "Transaction has no nonce but the artifact expects nonce {}",
+                expected
+            )
+            .into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check that the fee configuration the artifact was built with is
+    /// still current, since the transaction fee is fixed when it is
+    /// built.
+    pub fn check_fee_config(&self, current: &TxnFeeConfig) -> Result {
+        match &self.fee_config {
+            Some(fee_config) if fee_config != current => Err(
+                "Transaction fee configuration has changed since the artifact was built, \
+                 build the transaction again"
+                    .into(),
+            ),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use super::artifact::Artifact;
+use crate::{
+    cmd::{
+        api_url, burn, get_nonce_for, get_txn_fees, htlc, load_wallet, pay, print_json, securities,
+        Opts,
+    },
+    nonce::NonceKind,
+    result::Result,
+    traits::TxnEnvelope,
+};
+use helium_api::Client;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+/// Build an unsigned transaction artifact for the wallet. Only the
+/// public part of the wallet is used, so no password is required. The
+/// nonce and fee configuration are fetched from the API and stored in
+/// the artifact so it can be signed offline with "txn sign" and
+/// submitted with "txn submit". The nonce is reserved in the local
+/// nonce store so other transactions built or submitted meanwhile do
+/// not reuse it. The --commit option of the given transaction is
+/// ignored.
+pub struct Build {
+    /// Number of hours the nonce stays reserved for the artifact, which
+    /// should cover the time it takes to sign and submit it
+    #[structopt(long, default_value = "168")]
+    reserve_hours: u64,
+
+    #[structopt(subcommand)]
+    cmd: BuildCmd,
+}
+
+#[derive(Debug, StructOpt)]
+pub enum BuildCmd {
+    Pay(pay::Cmd),
+    Burn(burn::Cmd),
+    HtlcCreate(htlc::Create),
+    HtlcRedeem(htlc::Redeem),
+    SecuritiesTransfer(securities::Transfer),
+}
+
+impl Build {
+    pub fn run(&self, opts: Opts) -> Result {
+        let wallet = load_wallet(opts.files)?;
+        let client = Client::new_with_base_url(api_url());
+        let fee_config = get_txn_fees(&client, &opts.vars_file)?;
+        let address = wallet.pubkey_bin;
+        let timeout = self.reserve_hours * 60 * 60;
+        let get_nonce = |kind, nonce| get_nonce_for(&client, &address, kind, nonce, true, timeout);
+
+        let (envelope, nonce) = match &self.cmd {
+            BuildCmd::Pay(cmd) => {
+                let request = cmd.payment_request()?;
+                let nonce = get_nonce(NonceKind::Hnt, cmd.nonce)?;
+                let txn =
+                    cmd.mk_txn(&client, address, nonce.nonce, &fee_config, request.as_ref())?;
+                (txn.in_envelope(), Some(nonce))
+            }
+            BuildCmd::Burn(cmd) => {
+                let nonce = get_nonce(NonceKind::Hnt, cmd.nonce)?;
+                let txn = cmd.mk_txn(address, nonce.nonce, &fee_config)?;
+                (txn.in_envelope(), Some(nonce))
+            }
+            BuildCmd::HtlcCreate(cmd) => {
+                let nonce = get_nonce(NonceKind::Hnt, cmd.nonce)?;
+                let txn = cmd.mk_txn(address, nonce.nonce, &fee_config)?;
+                (txn.in_envelope(), Some(nonce))
+            }
+            BuildCmd::HtlcRedeem(cmd) => {
+                let txn = cmd.mk_txn(address, &fee_config)?;
+                (txn.in_envelope(), None)
+            }
+            BuildCmd::SecuritiesTransfer(cmd) => {
+                let nonce = get_nonce(NonceKind::Security, cmd.nonce)?;
+                let txn = cmd.mk_txn(address, nonce.nonce, &fee_config)?;
+                (txn.in_envelope(), Some(nonce))
+            }
+        };
+        let artifact = Artifact::build(&envelope, nonce.as_ref().map(|n| n.nonce), fee_config)?;
+        print_json(&artifact)?;
+        // Keep the nonce reserved for the transaction once the artifact
+        // has been handed out
+        if let Some(nonce) = nonce {
+            nonce.keep();
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,310 @@
+use crate::{
+    cmd::{
+        api_url, check_policy, data_dir, get_chain_keys, get_password, get_txn_fees, load_wallet,
+        print_footer, print_json, print_table, read_txn, record_journal, record_signed_spend,
+        status_json, wait_for_txn, write_output, CommitOpts, Opts, OutputFormat, QrOpts,
+    },
+    journal::{Journal, JournalStatus},
+    result::Result,
+    traits::{
+        txn_validate,
+        txn_verify::{SignatureCheck, SignatureStatus},
+        ToJson, TxnSign, TxnVerify, B64,
+    },
+};
+use helium_api::{BlockchainTxn, Client, PendingTxnStatus};
+use prettytable::{format, Table};
+use serde_json::json;
+use std::{path::PathBuf, time::Duration};
+use structopt::StructOpt;
+
+pub mod artifact;
+pub mod build;
+
+use artifact::Artifact;
+
+#[derive(Debug, StructOpt)]
+/// Work with encoded transactions
+pub enum Cmd {
+    Inspect(Inspect),
+    Verify(Verify),
+    Build(build::Build),
+    Sign(Sign),
+    Submit(Submit),
+    Resubmit(Resubmit),
+    Status(Status),
+}
+
+#[derive(Debug, StructOpt)]
+/// Decode and display a base64 encoded transaction without
+/// submitting it.
+pub struct Inspect {
+    /// Base64 encoded transaction, a file containing the transaction
+    /// or "-" to read the transaction from stdin.
+    #[structopt(name = "TRANSACTION")]
+    txn: Option<String>,
+
+    #[structopt(flatten)]
+    qr: QrOpts,
+}
+
+#[derive(Debug, StructOpt)]
+/// Verify all signatures on a base64 encoded transaction without
+/// submitting it. Exits with an error if any present signature is
+/// invalid or not made by a known signer. Proofs on chain variable
+/// transactions are checked against the keys currently on chain, which
+/// are fetched from the API unless --vars-file is given.
+pub struct Verify {
+    /// Base64 encoded transaction, a file containing the transaction
+    /// or "-" to read the transaction from stdin.
+    #[structopt(name = "TRANSACTION")]
+    txn: Option<String>,
+}
+
+#[derive(Debug, StructOpt)]
+/// Sign a transaction artifact as produced by "txn build". Every role
+/// in the transaction that belongs to the wallet is signed. This
+/// command does not access the network.
+pub struct Sign {
+    #[structopt(name = "ARTIFACT FILE")]
+    artifact: PathBuf,
+
+    /// File to write the signed artifact to. The signed artifact is
+    /// printed if no output file is given.
+    #[structopt(short = "o", long = "output")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, StructOpt)]
+/// Submit a signed transaction artifact to the Helium API. Fails if the
+/// fee configuration changed since the artifact was built. The
+/// transaction is not submitted unless the '--commit' option is given.
+pub struct Submit {
+    #[structopt(name = "ARTIFACT FILE")]
+    artifact: PathBuf,
+
+    #[structopt(flatten)]
+    commit: CommitOpts,
+}
+
+#[derive(Debug, StructOpt)]
+/// Submit a transaction from the local journal of the wallet again,
+/// for example after it was dropped by the API. The transaction is not
+/// submitted unless the '--commit' option is given.
+pub struct Resubmit {
+    /// The hash of the transaction in the journal
+    hash: String,
+
+    #[structopt(flatten)]
+    commit: CommitOpts,
+}
+
+#[derive(Debug, StructOpt)]
+/// Wait for a submitted transaction to clear or fail and display its
+/// final status. Exits with an error if the transaction failed.
+pub struct Status {
+    /// The hash of the submitted transaction
+    hash: String,
+
+    /// Number of seconds to wait for the transaction
+    #[structopt(long, default_value = "300")]
+    timeout: u64,
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        match self {
+            Cmd::Inspect(cmd) => cmd.run(opts),
+            Cmd::Verify(cmd) => cmd.run(opts),
+            Cmd::Build(cmd) => cmd.run(opts),
+            Cmd::Sign(cmd) => cmd.run(opts),
+            Cmd::Submit(cmd) => cmd.run(opts),
+            Cmd::Resubmit(cmd) => cmd.run(opts),
+            Cmd::Status(cmd) => cmd.run(opts),
+        }
+    }
+}
+
+impl Inspect {
+    pub fn run(&self, opts: Opts) -> Result {
+        let envelope = read_txn(&self.txn)?;
+        print_txn(&envelope, &None, opts.format)?;
+        self.qr.write(&envelope.to_b64()?)
+    }
+}
+
+impl Sign {
+    pub fn run(&self, opts: Opts) -> Result {
+        let mut artifact = Artifact::load(&self.artifact)?;
+        let mut envelope = artifact.to_txn()?;
+
+        let password = get_password(false)?;
+        let wallet = load_wallet(opts.files)?;
+        let keypair = wallet.decrypt(password.as_bytes())?;
+
+        artifact.check_nonce(&envelope)?;
+        check_policy(&keypair.pubkey_bin(), &envelope)?;
+        if envelope.sign_roles(&keypair)?.is_empty() {
+            return Err(format!(
+                "{} is not a signer of this transaction",
+                keypair.pubkey_bin()
+            )
+            .into());
+        }
+        record_signed_spend(&envelope)?;
+        record_journal(&keypair.pubkey_bin(), &envelope, JournalStatus::Signed);
+        artifact.set_txn(&envelope)?;
+        match &self.output {
+            Some(output) => write_output(output, &serde_json::to_vec_pretty(&artifact)?),
+            None => print_json(&artifact),
+        }
+    }
+}
+
+impl Submit {
+    pub fn run(&self, opts: Opts) -> Result {
+        let artifact = Artifact::load(&self.artifact)?;
+        let envelope = artifact.to_txn()?;
+        artifact.check_nonce(&envelope)?;
+        let client = Client::new_with_base_url(api_url());
+        let chain_keys = get_chain_keys(&client, &opts.vars_file, &envelope)?;
+        let unsigned: Vec<&str> = envelope
+            .verify_signatures_with(chain_keys.as_ref())?
+            .iter()
+            .filter(|check| !matches!(check.status, SignatureStatus::Valid))
+            .map(|check| check.role)
+            .collect();
+        if !unsigned.is_empty() {
+            return Err(format!(
+                "Transaction is missing valid signatures for: {}",
+                unsigned.join(", ")
+            )
+            .into());
+        }
+
+        artifact.check_fee_config(&get_txn_fees(&client, &opts.vars_file)?)?;
+        // A malformed fee payer of a supported transaction is an error
+        if txn_validate::is_supported(&envelope) {
+            self.commit.validate(&client, &envelope)?;
+        }
+        let status = self.commit.submit(&client, &envelope, None)?;
+        print_txn(&envelope, &status, opts.format)?;
+        print_footer(&status)?;
+        self.commit.maybe_wait(&status, opts.format)
+    }
+}
+
+impl Resubmit {
+    pub fn run(&self, opts: Opts) -> Result {
+        let wallet = load_wallet(opts.files)?;
+        let journal = Journal::open(&data_dir()?, &wallet.pubkey_bin)?;
+        let entry = journal
+            .find(&self.hash)?
+            .ok_or_else(|| format!("Transaction {} not found in journal", self.hash))?;
+        if entry.status == JournalStatus::Built {
+            return Err(format!("Transaction {} was never signed", self.hash).into());
+        }
+        let envelope = entry.to_txn()?;
+
+        let client = Client::new_with_base_url(api_url());
+        // A malformed fee payer of a supported transaction is an error
+        if txn_validate::is_supported(&envelope) {
+            self.commit.validate(&client, &envelope)?;
+        }
+        let status = self.commit.submit(&client, &envelope, None)?;
+        print_txn(&envelope, &status, opts.format)?;
+        print_footer(&status)?;
+        self.commit.maybe_wait(&status, opts.format)
+    }
+}
+
+impl Status {
+    pub fn run(&self, opts: Opts) -> Result {
+        wait_for_txn(&self.hash, Duration::from_secs(self.timeout), opts.format)
+    }
+}
+
+impl Verify {
+    pub fn run(&self, opts: Opts) -> Result {
+        let envelope = read_txn(&self.txn)?;
+        let client = Client::new_with_base_url(api_url());
+        let chain_keys = get_chain_keys(&client, &opts.vars_file, &envelope)?;
+        let checks = envelope.verify_signatures_with(chain_keys.as_ref())?;
+        print_checks(&checks, opts.format)?;
+        if checks.iter().any(|check| {
+            matches!(
+                check.status,
+                SignatureStatus::Invalid | SignatureStatus::UnknownSigner
+            )
+        }) {
+            return Err("Transaction has invalid signatures".into());
+        }
+        Ok(())
+    }
+}
+
+fn print_checks(checks: &[SignatureCheck], format: OutputFormat) -> Result {
+    let signer_str = |check: &SignatureCheck| {
+        check
+            .signer
+            .map_or_else(|| "unknown".to_string(), |s| s.to_string())
+    };
+    match format {
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+            table.set_titles(row!["Role", "Signer", "Status"]);
+            for check in checks {
+                table.add_row(row![check.role, signer_str(check), check.status]);
+            }
+            print_table(&table)
+        }
+        OutputFormat::Json => {
+            let mut table = Vec::with_capacity(checks.len());
+            for check in checks {
+                table.push(json!({
+                    "role": check.role,
+                    "signer": check.signer.map(|s| s.to_string()),
+                    "status": check.status.to_string(),
+                }));
+            }
+            print_json(&table)
+        }
+    }
+}
+
+fn print_txn(
+    envelope: &BlockchainTxn,
+    status: &Option<PendingTxnStatus>,
+    format: OutputFormat,
+) -> Result {
+    let mut json = envelope.to_json()?;
+    json["hash"] = status_json(envelope, status)?;
+    match format {
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.add_row(row!["Key", "Value"]);
+            if let Some(map) = json.as_object() {
+                for (key, value) in map {
+                    table.add_row(row![key, json_value_str(value)?]);
+                }
+            }
+            print_table(&table)
+        }
+        OutputFormat::Json => {
+            json["txn"] = envelope.to_b64()?.into();
+            print_json(&json)
+        }
+    }
+}
+
+fn json_value_str(value: &serde_json::Value) -> Result<String> {
+    match value {
+        serde_json::Value::Null => Ok("none".to_string()),
+        serde_json::Value::String(s) => Ok(s.to_string()),
+        serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
+            Ok(serde_json::to_string_pretty(value)?)
+        }
+        _ => Ok(value.to_string()),
+    }
+}
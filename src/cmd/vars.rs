@@ -1,17 +1,28 @@
 use crate::{
-    cmd::{api_url, multisig::Artifact, print_json, Opts},
+    cmd::{api_url, get_vars, print_json, txn::artifact::Artifact, Opts},
     keypair::PubKeyBin,
     result::Result,
     traits::{ToJson, TxnEnvelope},
 };
 use helium_api::{BlockchainTxnVarsV1, BlockchainVarV1, Client};
-use std::{convert::TryInto, str::FromStr};
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    convert::TryInto,
+    fs,
+    path::Path,
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 use structopt::StructOpt;
 
+/// The age after which a chain variables snapshot is considered stale
+const STALE_SNAPSHOT_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
 #[derive(Debug, StructOpt)]
 /// Commands for chain variables
 pub enum Cmd {
     Current(Current),
+    Snapshot(Snapshot),
     Create(Create),
 }
 
@@ -19,6 +30,14 @@ pub enum Cmd {
 /// Lists current chain variables
 pub struct Current {}
 
+#[derive(Debug, StructOpt)]
+/// Output a snapshot of the current chain variables, the last
+/// allocated OUI and the block height they were fetched at. The
+/// snapshot can be passed with the global --vars-file option to
+/// calculate fees and build transactions without fetching chain
+/// variables from the API.
+pub struct Snapshot {}
+
 #[derive(Debug, StructOpt)]
 /// Create a chain variable transaction
 pub struct Create {
@@ -51,26 +70,75 @@ impl Cmd {
     pub fn run(&self, opts: Opts) -> Result {
         match self {
             Cmd::Current(cmd) => cmd.run(opts),
+            Cmd::Snapshot(cmd) => cmd.run(opts),
             Cmd::Create(cmd) => cmd.run(opts),
         }
     }
 }
 
 impl Current {
+    pub fn run(&self, opts: Opts) -> Result {
+        let client = Client::new_with_base_url(api_url());
+        print_json(&get_vars(&client, &opts.vars_file)?)
+    }
+}
+
+impl Snapshot {
     pub fn run(&self, _opts: Opts) -> Result {
-        print_json(&get_vars()?)
+        let client = Client::new_with_base_url(api_url());
+        print_json(&VarsSnapshot::fetch(&client)?)
     }
 }
 
-fn get_vars() -> Result<serde_json::Map<String, serde_json::Value>> {
-    let client = Client::new_with_base_url(api_url());
-    client.get_vars()
+/// A snapshot of the chain variables at a given block height
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VarsSnapshot {
+    /// The block height the snapshot was taken at
+    pub height: u64,
+    /// The time the snapshot was taken in seconds since the epoch
+    pub time: u64,
+    pub last_oui: u64,
+    pub vars: serde_json::Map<String, serde_json::Value>,
+}
+
+impl VarsSnapshot {
+    pub fn fetch(client: &Client) -> Result<Self> {
+        Ok(Self {
+            height: client.get_height()?,
+            time: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+            last_oui: client.get_last_oui()?,
+            vars: client.get_vars()?,
+        })
+    }
+
+    /// Load a snapshot from the given file. A warning is printed if
+    /// the snapshot is stale.
+    pub fn load(path: &Path) -> Result<Self> {
+        let snapshot: Self = serde_json::from_slice(&fs::read(path)?)
+            .map_err(|err| format!("Invalid vars snapshot {}: {}", path.display(), err))?;
+        if let Some(age) = snapshot.age() {
+            if age > STALE_SNAPSHOT_AGE {
+                eprintln!(
+                    "warning: vars snapshot from height {} is {} hours old",
+                    snapshot.height,
+                    age.as_secs() / 3600
+                );
+            }
+        }
+        Ok(snapshot)
+    }
+
+    fn age(&self) -> Option<Duration> {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH + Duration::from_secs(self.time))
+            .ok()
+    }
 }
 
 impl Create {
-    pub fn run(&self, _opts: Opts) -> Result {
+    pub fn run(&self, opts: Opts) -> Result {
         let client = Client::new_with_base_url(api_url());
-        let vars = client.get_vars()?;
+        let vars = get_vars(&client, &opts.vars_file)?;
         let mut txn = BlockchainTxnVarsV1 {
             version_predicate: 0,
             master_key: vec![],
@@ -0,0 +1,322 @@
+use crate::{keypair::PubKeyBin, result::Result};
+use fs2::FileExt;
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+/// A numbered invoice issued by a wallet
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Invoice {
+    pub number: u64,
+    /// Unix time the invoice was created at
+    pub created: u64,
+    /// The invoiced amount in bones
+    pub amount: u64,
+    pub label: Option<String>,
+    /// Unix time after which the invoice should no longer be paid
+    pub expires: Option<u64>,
+    /// The address the invoice is expected to be paid from, if known
+    #[serde(default)]
+    pub payer: Option<String>,
+    /// The signed payment request URI of the invoice
+    pub request: String,
+    /// The payment that paid the invoice, once one has been found
+    pub payment: Option<ReceivedPayment>,
+}
+
+impl Invoice {
+    pub fn is_expired(&self, now: u64) -> bool {
+        matches!(self.expires, Some(expires) if now >= expires)
+    }
+
+    /// Whether the given payment could pay this invoice
+    fn accepts(&self, payment: &ReceivedPayment) -> bool {
+        self.payment.is_none()
+            && self.amount == payment.amount
+            && self.created <= payment.time
+            && !self.is_expired(payment.time)
+            && match &self.payer {
+                Some(payer) => payer == &payment.payer,
+                None => true,
+            }
+    }
+}
+
+/// A payment received by a wallet
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ReceivedPayment {
+    pub hash: String,
+    /// Unix time of the block the payment cleared in
+    pub time: u64,
+    pub payer: String,
+    /// The amount received in bones
+    pub amount: u64,
+}
+
+impl ReceivedPayment {
+    /// The payment to the given address in an activity transaction as
+    /// returned by the API, if the transaction is a payment to it.
+    pub fn from_activity(address: &str, txn: &serde_json::Value) -> Option<Self> {
+        let amount = match txn["type"].as_str()? {
+            "payment_v1" if txn["payee"].as_str() == Some(address) => txn["amount"].as_u64()?,
+            "payment_v2" => txn["payments"]
+                .as_array()?
+                .iter()
+                .filter(|payment| payment["payee"].as_str() == Some(address))
+                .filter_map(|payment| payment["amount"].as_u64())
+                .sum(),
+            _ => return None,
+        };
+        if amount == 0 {
+            return None;
+        }
+        Some(Self {
+            hash: txn["hash"].as_str()?.to_string(),
+            time: txn["time"].as_u64()?,
+            payer: txn["payer"].as_str()?.to_string(),
+            amount,
+        })
+    }
+}
+
+/// The invoices issued by a wallet, kept in a JSON file that is locked
+/// while it is updated.
+pub struct InvoiceBook {
+    path: PathBuf,
+}
+
+impl InvoiceBook {
+    pub fn open(dir: &Path, address: &PubKeyBin) -> Result<Self> {
+        let dir = dir.join("invoices");
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            path: dir.join(format!("{}.json", address)),
+        })
+    }
+
+    /// All invoices in the order they were created
+    pub fn invoices(&self) -> Result<Vec<Invoice>> {
+        self.update(|invoices| Ok(invoices.clone()))
+    }
+
+    /// Add a new invoice with the next invoice number, which is passed
+    /// to the given function that creates the invoice.
+    pub fn create<F>(&self, f: F) -> Result<Invoice>
+    where
+        F: FnOnce(u64) -> Result<Invoice>,
+    {
+        self.update(|invoices| {
+            let number = invoices.iter().map(|i| i.number).max().unwrap_or(0) + 1;
+            let invoice = f(number)?;
+            invoices.push(invoice.clone());
+            Ok(invoice)
+        })
+    }
+
+    pub fn update<F, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&mut Vec<Invoice>) -> Result<R>,
+    {
+        let mut file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&self.path)?;
+        file.lock_exclusive()?;
+        let mut data = String::new();
+        file.read_to_string(&mut data)?;
+        let mut invoices: Vec<Invoice> = if data.trim().is_empty() {
+            vec![]
+        } else {
+            serde_json::from_str(&data)?
+        };
+        let result = f(&mut invoices)?;
+        file.seek(SeekFrom::Start(0))?;
+        file.set_len(0)?;
+        file.write_all(&serde_json::to_vec_pretty(&invoices)?)?;
+        file.unlock()?;
+        Ok(result)
+    }
+}
+
+/// A payment that could pay more than one open invoice
+#[derive(Debug, Clone, PartialEq)]
+pub struct AmbiguousPayment {
+    pub payment: ReceivedPayment,
+    /// The numbers of the invoices the payment could pay
+    pub invoices: Vec<u64>,
+}
+
+/// The result of matching payments to invoices
+#[derive(Debug, Default)]
+pub struct Matches {
+    /// The number of invoices that were newly paid
+    pub paid: usize,
+    /// Payments that were not matched since they could pay several
+    /// invoices
+    pub ambiguous: Vec<AmbiguousPayment>,
+}
+
+/// Match received payments to unpaid invoices. Payments have no memo,
+/// so a payment can pay an unpaid invoice for exactly the same amount
+/// that was created before the payment, had not expired when it was
+/// paid and, if the invoice names a payer, is from that payer. An
+/// invoice naming the payer of the payment is preferred over one that
+/// names no payer. A payment that could pay more than one invoice is
+/// left unmatched and reported as ambiguous rather than guessing which
+/// invoice it was meant for. Every payment pays at most one invoice.
+pub fn match_payments(invoices: &mut [Invoice], payments: &[ReceivedPayment]) -> Matches {
+    let mut matches = Matches::default();
+    let mut payments: Vec<&ReceivedPayment> = payments
+        .iter()
+        .filter(|payment| {
+            !invoices
+                .iter()
+                .filter_map(|invoice| invoice.payment.as_ref())
+                .any(|paid| paid.hash == payment.hash)
+        })
+        .collect();
+    payments.sort_by_key(|payment| payment.time);
+    for payment in payments {
+        let candidates: Vec<usize> = (0..invoices.len())
+            .filter(|&index| invoices[index].accepts(payment))
+            .collect();
+        let pinned: Vec<usize> = candidates
+            .iter()
+            .copied()
+            .filter(|&index| invoices[index].payer.is_some())
+            .collect();
+        let candidates = if pinned.is_empty() {
+            candidates
+        } else {
+            pinned
+        };
+        match candidates.as_slice() {
+            [] => (),
+            [index] => {
+                invoices[*index].payment = Some(payment.clone());
+                matches.paid += 1;
+            }
+            _ => matches.ambiguous.push(AmbiguousPayment {
+                payment: payment.clone(),
+                invoices: candidates
+                    .iter()
+                    .map(|&index| invoices[index].number)
+                    .collect(),
+            }),
+        }
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invoice(number: u64, created: u64, amount: u64, expires: Option<u64>) -> Invoice {
+        Invoice {
+            number,
+            created,
+            amount,
+            label: None,
+            expires,
+            payer: None,
+            request: String::new(),
+            payment: None,
+        }
+    }
+
+    fn payment(hash: &str, time: u64, amount: u64) -> ReceivedPayment {
+        ReceivedPayment {
+            hash: hash.to_string(),
+            time,
+            payer: "p".to_string(),
+            amount,
+        }
+    }
+
+    fn paid(invoices: &[Invoice]) -> Vec<Option<&str>> {
+        invoices
+            .iter()
+            .map(|i| i.payment.as_ref().map(|p| p.hash.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn match_invoices() {
+        let mut invoices = vec![
+            invoice(1, 100, 500, None),
+            invoice(2, 100, 600, None),
+            invoice(3, 100, 700, Some(200)),
+            invoice(4, 300, 900, None),
+        ];
+        let payments = vec![
+            payment("late", 250, 700),
+            payment("early", 50, 900),
+            payment("b", 160, 600),
+            payment("a", 150, 500),
+        ];
+        let matches = match_payments(&mut invoices, &payments);
+        assert_eq!(matches.paid, 2);
+        assert!(matches.ambiguous.is_empty());
+        assert_eq!(paid(&invoices), vec![Some("a"), Some("b"), None, None]);
+        // Payments that already paid an invoice are not matched again
+        invoices.push(invoice(5, 100, 500, None));
+        assert_eq!(match_payments(&mut invoices, &payments).paid, 0);
+    }
+
+    #[test]
+    fn match_ambiguous_invoices() {
+        let mut invoices = vec![
+            invoice(1, 100, 500, None),
+            invoice(2, 100, 500, None),
+            invoice(3, 100, 500, None),
+        ];
+        invoices[2].payer = Some("q".to_string());
+        // Without a payer the payment could pay either open invoice
+        let matches = match_payments(&mut invoices, &[payment("a", 150, 500)]);
+        assert_eq!(matches.paid, 0);
+        assert_eq!(matches.ambiguous[0].invoices, vec![1, 2]);
+        assert_eq!(paid(&invoices), vec![None, None, None]);
+        // An invoice naming the payer is preferred
+        let mut from_q = payment("b", 160, 500);
+        from_q.payer = "q".to_string();
+        let matches = match_payments(&mut invoices, &[from_q]);
+        assert_eq!(matches.paid, 1);
+        assert_eq!(paid(&invoices), vec![None, None, Some("b")]);
+    }
+
+    #[test]
+    fn payments_from_activity() {
+        let v2 = json!({
+            "type": "payment_v2",
+            "hash": "h",
+            "time": 10,
+            "payer": "p",
+            "payments": [
+                {"payee": "me", "amount": 3},
+                {"payee": "other", "amount": 4},
+                {"payee": "me", "amount": 5},
+            ],
+        });
+        assert_eq!(
+            ReceivedPayment::from_activity("me", &v2),
+            Some(payment("h", 10, 8))
+        );
+        assert_eq!(ReceivedPayment::from_activity("nobody", &v2), None);
+        let v1 = json!({
+            "type": "payment_v1",
+            "hash": "h",
+            "time": 10,
+            "payer": "p",
+            "payee": "me",
+            "amount": 3,
+        });
+        assert_eq!(ReceivedPayment::from_activity("me", &v1).unwrap().amount, 3);
+        assert_eq!(ReceivedPayment::from_activity("p", &v1), None);
+    }
+}
@@ -0,0 +1,260 @@
+use crate::{
+    keypair::PubKeyBin,
+    result::Result,
+    traits::{ToJson, TxnHash, B64},
+};
+use fs2::FileExt;
+use helium_api::{BlockchainTxn, Txn};
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    fmt, fs,
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The state of a transaction when it was recorded in the journal
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum JournalStatus {
+    /// Built but not yet signed by the wallet
+    Built,
+    /// Signed for a preview without --commit, never meant to be
+    /// submitted
+    Preview,
+    /// Signed with `txn sign` to be submitted later
+    Signed,
+    /// Submitted to the API
+    Submitted,
+    /// Rejected by the API when submitted
+    Failed,
+}
+
+impl fmt::Display for JournalStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Self::Built => "built",
+            Self::Preview => "preview",
+            Self::Signed => "signed",
+            Self::Submitted => "submitted",
+            Self::Failed => "failed",
+        };
+        f.write_str(s)
+    }
+}
+
+/// A single line in the journal of a wallet
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JournalEntry {
+    /// Unix time the entry was recorded at
+    pub time: u64,
+    /// The transaction hash in the same form the API uses
+    pub hash: String,
+    #[serde(rename = "type")]
+    pub txn_type: String,
+    pub nonce: Option<u64>,
+    pub status: JournalStatus,
+    /// The reason submitting the transaction failed
+    pub error: Option<String>,
+    /// The base64 encoded transaction envelope
+    pub txn: String,
+}
+
+impl JournalEntry {
+    pub fn new(envelope: &BlockchainTxn, status: JournalStatus) -> Result<Self> {
+        Ok(Self {
+            time: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+            hash: envelope.txn_hash()?.to_b64_url()?,
+            txn_type: envelope.to_json()?["type"]
+                .as_str()
+                .unwrap_or("unknown")
+                .to_string(),
+            nonce: txn_nonce(envelope),
+            status,
+            error: None,
+            txn: envelope.to_b64()?,
+        })
+    }
+
+    pub fn to_txn(&self) -> Result<BlockchainTxn> {
+        BlockchainTxn::from_b64(&self.txn)
+    }
+}
+
+/// The nonce of the wallet paying for a transaction, if it has one.
+/// The nonce of a chain variables transaction is the chain variable
+/// nonce rather than an account nonce, so it is not included.
+pub fn txn_nonce(envelope: &BlockchainTxn) -> Option<u64> {
+    match &envelope.txn {
+        Some(Txn::Payment(t)) => Some(t.nonce),
+        Some(Txn::PaymentV2(t)) => Some(t.nonce),
+        Some(Txn::CreateHtlc(t)) => Some(t.nonce),
+        Some(Txn::TokenBurn(t)) => Some(t.nonce),
+        Some(Txn::SecurityExchange(t)) => Some(t.nonce),
+        Some(Txn::AssertLocation(t)) => Some(t.nonce),
+        Some(Txn::TransferHotspot(t)) => Some(t.buyer_nonce),
+        _ => None,
+    }
+}
+
+/// An append-only local journal of the transactions built, signed and
+/// submitted by a wallet. Every change in the status of a transaction
+/// is appended as a new JSON line, so the latest entry for a hash
+/// holds its current status.
+pub struct Journal {
+    path: PathBuf,
+}
+
+impl Journal {
+    pub fn open(dir: &Path, address: &PubKeyBin) -> Result<Self> {
+        let dir = dir.join("journal");
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            path: dir.join(format!("{}.jsonl", address)),
+        })
+    }
+
+    /// Open the journals of all wallets that have recorded the
+    /// transaction with the given hash.
+    pub fn find_all(dir: &Path, hash: &str) -> Result<Vec<Self>> {
+        let dir = dir.join("journal");
+        if !dir.exists() {
+            return Ok(vec![]);
+        }
+        let mut journals = vec![];
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("jsonl") {
+                continue;
+            }
+            let journal = Self { path };
+            if journal.find(hash)?.is_some() {
+                journals.push(journal);
+            }
+        }
+        Ok(journals)
+    }
+
+    pub fn append(&self, entry: &JournalEntry) -> Result {
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.lock_exclusive()?;
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        file.write_all(&line)?;
+        file.unlock()?;
+        Ok(())
+    }
+
+    /// All entries in the order they were recorded
+    pub fn entries(&self) -> Result<Vec<JournalEntry>> {
+        if !self.path.exists() {
+            return Ok(vec![]);
+        }
+        let reader = BufReader::new(fs::File::open(&self.path)?);
+        let mut entries = vec![];
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            entries.push(serde_json::from_str(&line)?);
+        }
+        Ok(entries)
+    }
+
+    /// The latest entry of every transaction in the journal, in the
+    /// order the transactions were first recorded
+    pub fn latest(&self) -> Result<Vec<JournalEntry>> {
+        let mut latest: Vec<JournalEntry> = vec![];
+        for entry in self.entries()? {
+            match latest.iter_mut().find(|e| e.hash == entry.hash) {
+                Some(existing) => *existing = entry,
+                None => latest.push(entry),
+            }
+        }
+        Ok(latest)
+    }
+
+    /// The latest entry for the transaction with the given hash
+    pub fn find(&self, hash: &str) -> Result<Option<JournalEntry>> {
+        Ok(self
+            .entries()?
+            .into_iter()
+            .rev()
+            .find(|entry| entry.hash == hash))
+    }
+}
+
+/// Record the given transaction in the journal of the given wallet
+pub fn record(
+    dir: &Path,
+    address: &PubKeyBin,
+    envelope: &BlockchainTxn,
+    status: JournalStatus,
+) -> Result {
+    Journal::open(dir, address)?.append(&JournalEntry::new(envelope, status)?)
+}
+
+/// Record a new status for the given transaction in the journals of
+/// every wallet that has recorded it before.
+pub fn update(
+    dir: &Path,
+    envelope: &BlockchainTxn,
+    status: JournalStatus,
+    error: Option<String>,
+) -> Result {
+    let mut entry = JournalEntry::new(envelope, status)?;
+    entry.error = error;
+    for journal in Journal::find_all(dir, &entry.hash)? {
+        journal.append(&entry)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keypair::Keypair, traits::TxnEnvelope};
+    use helium_api::{BlockchainTxnPaymentV2, Payment};
+
+    #[test]
+    fn journal_latest() {
+        let dir = std::env::temp_dir().join(format!("journal-test-{}", std::process::id()));
+        let wallet = Keypair::gen_keypair().pubkey_bin();
+        let txn = |nonce| {
+            BlockchainTxnPaymentV2 {
+                payer: wallet.into(),
+                payments: vec![Payment {
+                    payee: wallet.into(),
+                    amount: 1,
+                }],
+                nonce,
+                fee: 0,
+                signature: vec![],
+            }
+            .in_envelope()
+        };
+        record(&dir, &wallet, &txn(1), JournalStatus::Built).unwrap();
+        record(&dir, &wallet, &txn(2), JournalStatus::Signed).unwrap();
+        update(&dir, &txn(1), JournalStatus::Submitted, None).unwrap();
+        // Transactions not recorded by any wallet are not journaled
+        update(&dir, &txn(3), JournalStatus::Submitted, None).unwrap();
+
+        let journal = Journal::open(&dir, &wallet).unwrap();
+        let entries = journal.entries();
+        let latest = journal.latest();
+        // Clean up before asserting so a failure doesn't leave the
+        // directory behind
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(entries.unwrap().len(), 3);
+        let latest = latest.unwrap();
+        assert_eq!(latest.len(), 2);
+        assert_eq!(latest[0].nonce, Some(1));
+        assert_eq!(latest[0].status, JournalStatus::Submitted);
+        assert_eq!(latest[1].status, JournalStatus::Signed);
+    }
+}
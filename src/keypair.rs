@@ -5,12 +5,21 @@ use crate::{
 use byteorder::ReadBytesExt;
 use ed25519::Signature;
 pub use ed25519::{PublicKey, SecretKey, Seed};
-use sodiumoxide::crypto::sign::ed25519;
-use std::{fmt, io, str::FromStr};
+use sodiumoxide::crypto::{box_, sign::ed25519};
+use std::{convert::TryFrom, fmt, io, str::FromStr};
 
 static START: std::sync::Once = std::sync::Once::new();
 pub const KEYTYPE_ED25519: u8 = 1;
 
+// Protobuf encoded libp2p ed25519 public key header (key type 1,
+// 32 bytes of key data) prefixed with the identity multihash header
+const PEER_ID_ED25519_PREFIX: [u8; 6] = [0x00, 0x24, 0x08, 0x01, 0x12, 0x20];
+// DER encoded SubjectPublicKeyInfo header for an ed25519 key
+const SPKI_ED25519_PREFIX: [u8; 12] = [
+    0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+];
+const SSH_ED25519_KEYTYPE: &str = "ssh-ed25519";
+
 // Newtype to allow us to `impl Default` on a 33 element array.
 #[derive(Clone, Copy)]
 pub struct PubKeyBin(pub(crate) [u8; 33]);
@@ -37,9 +46,90 @@ impl PubKeyBin {
 
     pub fn from_vec(data: &[u8]) -> Self {
         let mut result = PubKeyBin::default();
-        result.0.copy_from_slice(&data);
+        result.0.copy_from_slice(data);
         result
     }
+
+    /// Like `from_vec`, but returns an error instead of panicking if
+    /// the given data is not the size of a key.
+    pub fn try_from_vec(data: &[u8]) -> Result<Self> {
+        if data.len() != 33 {
+            return Err(format!("Invalid public key length {}", data.len()).into());
+        }
+        Ok(Self::from_vec(data))
+    }
+
+    fn ed25519_key(&self) -> Result<&[u8]> {
+        if self.0[0] != KEYTYPE_ED25519 {
+            return Err(format!("Invalid key type {}", self.0[0]).into());
+        }
+        Ok(&self.0[1..])
+    }
+
+    /// Convert the ed25519 key in this key into the X25519 key used
+    /// for encrypting messages to this address.
+    pub fn to_curve25519(&self) -> Result<box_::PublicKey> {
+        let public_key = PublicKey::from_slice(self.ed25519_key()?).ok_or("Invalid public key")?;
+        ed25519::to_curve25519_pk(&public_key)
+            .map_err(|_| "Failed to convert public key to curve25519".into())
+    }
+
+    /// Encode this key as a libp2p peer id, i.e. the base58 encoded
+    /// identity multihash of the protobuf encoded public key.
+    pub fn to_peer_id(&self) -> Result<String> {
+        let mut data = PEER_ID_ED25519_PREFIX.to_vec();
+        data.extend_from_slice(self.ed25519_key()?);
+        Ok(bs58::encode(data).into_string())
+    }
+
+    pub fn from_peer_id(peer_id: &str) -> Result<Self> {
+        let data = bs58::decode(peer_id).into_vec()?;
+        if data.len() != PEER_ID_ED25519_PREFIX.len() + 32
+            || data[..PEER_ID_ED25519_PREFIX.len()] != PEER_ID_ED25519_PREFIX
+        {
+            return Err(format!("Unsupported peer id {}", peer_id).into());
+        }
+        let mut result = PubKeyBin::default();
+        result.0[0] = KEYTYPE_ED25519;
+        result.0[1..].copy_from_slice(&data[PEER_ID_ED25519_PREFIX.len()..]);
+        Ok(result)
+    }
+
+    /// Encode this key as a PEM encoded SubjectPublicKeyInfo as used
+    /// by OpenSSL.
+    pub fn to_pem(&self) -> Result<String> {
+        let mut der = SPKI_ED25519_PREFIX.to_vec();
+        der.extend_from_slice(self.ed25519_key()?);
+        Ok(format!(
+            "-----BEGIN PUBLIC KEY-----\n{}\n-----END PUBLIC KEY-----",
+            base64::encode(&der)
+        ))
+    }
+
+    /// Encode this key as an OpenSSH authorized_keys style public key.
+    pub fn to_ssh(&self) -> Result<String> {
+        let mut data = vec![];
+        for field in &[SSH_ED25519_KEYTYPE.as_bytes(), self.ed25519_key()?] {
+            data.extend_from_slice(&(field.len() as u32).to_be_bytes());
+            data.extend_from_slice(field);
+        }
+        Ok(format!("{} {}", SSH_ED25519_KEYTYPE, base64::encode(&data)))
+    }
+
+    /// Encode this key as a JSON Web Key (RFC 8037).
+    pub fn to_jwk(&self) -> Result<serde_json::Value> {
+        Ok(json!({
+            "kty": "OKP",
+            "crv": "Ed25519",
+            "x": base64::encode_config(self.ed25519_key()?, base64::URL_SAFE_NO_PAD),
+        }))
+    }
+
+    /// Encode the raw 32 byte ed25519 key, without the Helium key type
+    /// byte, as hex.
+    pub fn to_hex(&self) -> Result<String> {
+        Ok(hex::encode(self.ed25519_key()?))
+    }
 }
 
 impl From<PubKeyBin> for PublicKey {
@@ -95,7 +185,7 @@ pub struct Keypair {
     pub secret: SecretKey,
 }
 
-fn init() {
+pub(crate) fn init() {
     START.call_once(|| sodiumoxide::init().expect("Failed to intialize sodium"))
 }
 
@@ -119,12 +209,21 @@ impl Keypair {
     }
 
     pub fn sign(&self, data: &[u8]) -> Vec<u8> {
-        ed25519::sign_detached(data, &self.secret).0.to_vec()
+        ed25519::sign_detached(data, &self.secret)
+            .to_bytes()
+            .to_vec()
     }
 
     pub fn pubkey_bin(&self) -> PubKeyBin {
         PubKeyBin::from(&self.public)
     }
+
+    /// Convert the ed25519 secret key of this keypair into the X25519
+    /// secret key used to decrypt messages sent to this keypair.
+    pub fn to_curve25519(&self) -> Result<box_::SecretKey> {
+        ed25519::to_curve25519_sk(&self.secret)
+            .map_err(|_| "Failed to convert secret key to curve25519".into())
+    }
 }
 
 impl fmt::Display for Keypair {
@@ -178,7 +277,7 @@ pub trait Verify {
 
 impl Verify for PublicKey {
     fn verify(&self, message: &[u8], signature: &[u8]) -> Result {
-        if let Some(sig) = Signature::from_slice(signature) {
+        if let Ok(sig) = Signature::try_from(signature) {
             if ed25519::verify_detached(&sig, message, self) {
                 Ok(())
             } else {
@@ -193,7 +292,7 @@ impl Verify for PublicKey {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::io::Cursor;
+    use std::{convert::TryInto, io::Cursor};
 
     #[test]
     fn roundtrip_keypair() {
@@ -218,6 +317,39 @@ mod tests {
         assert_eq!(pk, decoded);
     }
 
+    #[test]
+    fn public_key_encodings() {
+        let pubkey_bin = PubKeyBin::from(&PublicKey(
+            hex::decode("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a")
+                .unwrap()
+                .as_slice()
+                .try_into()
+                .unwrap(),
+        ));
+        let peer_id = pubkey_bin.to_peer_id().expect("peer id");
+        assert_eq!(
+            peer_id,
+            "12D3KooWQK1wnefoLrcVHbbnf5tLzbopUd3K3bFAoJpA7YJgL5pV"
+        );
+        assert_eq!(pubkey_bin, PubKeyBin::from_peer_id(&peer_id).unwrap());
+        assert_eq!(
+            pubkey_bin.to_ssh().expect("ssh"),
+            "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAINdamAGCsQq31Uv+08lkBzoO4XLz2qYjJa8CGmj3B1Ea"
+        );
+        assert!(pubkey_bin
+            .to_pem()
+            .expect("pem")
+            .contains("MCowBQYDK2VwAyEA11qYAYKxCrfVS/7TyWQHOg7hcvPapiMlrwIaaPcHURo="));
+        assert_eq!(
+            pubkey_bin.to_jwk().expect("jwk")["x"],
+            "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"
+        );
+        assert_eq!(
+            pubkey_bin.to_hex().expect("hex"),
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a"
+        );
+    }
+
     #[test]
     fn roundtrip_b58_public_key() {
         let pk = Keypair::gen_keypair().public;
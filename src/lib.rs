@@ -7,12 +7,22 @@ extern crate lazy_static;
 #[macro_use]
 extern crate serde_json;
 
+pub mod amount;
+pub mod api;
 pub mod cmd;
 pub mod format;
+pub mod invoice;
+pub mod journal;
 pub mod keypair;
+pub mod memo;
 pub mod mnemonic;
+pub mod nonce;
+pub mod payment_request;
+pub mod policy;
 pub mod pwhash;
+pub mod qr;
 pub mod result;
+pub mod sealed;
 pub mod staking;
 pub mod traits;
 pub mod wallet;
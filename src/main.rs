@@ -1,7 +1,8 @@
 use helium_wallet::{
     cmd::{
-        balance, burn, create, hotspots, htlc, info, multisig, onboard, oracle, oui, pay, request,
-        securities, upgrade, vars, verify, Opts,
+        balance, burn, create, decrypt, encrypt, fees, history, hotspots, htlc, info, invoice,
+        keys, multisig, onboard, oracle, oui, pay, pending, request, securities, txn, upgrade,
+        vars, verify, Opts,
     },
     result::Result,
 };
@@ -33,8 +34,16 @@ pub enum Cmd {
     Securities(securities::Cmd),
     Burn(burn::Cmd),
     Multisig(multisig::Cmd),
+    Encrypt(encrypt::Cmd),
+    Decrypt(decrypt::Cmd),
+    Keys(keys::Cmd),
+    Txn(txn::Cmd),
     Request(request::Cmd),
+    Invoice(invoice::Cmd),
     Vars(vars::Cmd),
+    Fees(fees::Cmd),
+    History(history::Cmd),
+    Pending(pending::Cmd),
 }
 
 fn main() {
@@ -61,7 +70,15 @@ fn run(cli: Cli) -> Result {
         Cmd::Securities(cmd) => cmd.run(cli.opts),
         Cmd::Burn(cmd) => cmd.run(cli.opts),
         Cmd::Multisig(cmd) => cmd.run(cli.opts),
+        Cmd::Encrypt(cmd) => cmd.run(cli.opts),
+        Cmd::Decrypt(cmd) => cmd.run(cli.opts),
+        Cmd::Keys(cmd) => cmd.run(cli.opts),
+        Cmd::Txn(cmd) => cmd.run(cli.opts),
         Cmd::Request(cmd) => cmd.run(cli.opts),
+        Cmd::Invoice(cmd) => cmd.run(cli.opts),
         Cmd::Vars(cmd) => cmd.run(cli.opts),
+        Cmd::Fees(cmd) => cmd.run(cli.opts),
+        Cmd::History(cmd) => cmd.run(cli.opts),
+        Cmd::Pending(cmd) => cmd.run(cli.opts),
     }
 }
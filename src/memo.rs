@@ -0,0 +1,121 @@
+use crate::{result::Result, traits::B64};
+use serde_json::json;
+use structopt::clap::arg_enum;
+
+/// The maximum number of ASCII characters in a memo
+const MAX_ASCII_LEN: usize = 8;
+
+arg_enum! {
+    /// The ways a memo can be given on the command line
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum MemoFormat {
+        Base64,
+        Decimal,
+        Hex,
+        Ascii,
+    }
+}
+
+/// The 64 bit memo of a token burn. On chain the memo is an integer,
+/// which is conventionally displayed as the base64 encoding of its
+/// little endian bytes. ASCII memos are stored in those same bytes, so
+/// "abc" and the base64 "YWJjAAAAAAA=" are the same memo.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Memo(pub u64);
+
+impl Memo {
+    pub fn parse(s: &str, format: MemoFormat) -> Result<Self> {
+        let invalid = || {
+            format!(
+                "Invalid {} memo \"{}\"",
+                format.to_string().to_lowercase(),
+                s
+            )
+        };
+        match format {
+            MemoFormat::Base64 => Ok(Self(u64::from_b64(s).map_err(|_| invalid())?)),
+            MemoFormat::Decimal => Ok(Self(s.parse().map_err(|_| invalid())?)),
+            MemoFormat::Hex => {
+                let digits = s
+                    .strip_prefix("0x")
+                    .or_else(|| s.strip_prefix("0X"))
+                    .unwrap_or(s);
+                if digits.starts_with('+') {
+                    return Err(invalid().into());
+                }
+                Ok(Self(
+                    u64::from_str_radix(digits, 16).map_err(|_| invalid())?,
+                ))
+            }
+            MemoFormat::Ascii => {
+                if s.len() > MAX_ASCII_LEN || !s.is_ascii() {
+                    return Err(format!(
+                        "Invalid ascii memo \"{}\", expected at most {} ASCII characters",
+                        s, MAX_ASCII_LEN
+                    )
+                    .into());
+                }
+                let mut bytes = [0u8; 8];
+                bytes[..s.len()].copy_from_slice(s.as_bytes());
+                Ok(Self(u64::from_le_bytes(bytes)))
+            }
+        }
+    }
+
+    pub fn to_b64(&self) -> Result<String> {
+        self.0.to_b64()
+    }
+
+    pub fn to_hex(&self) -> String {
+        format!("{:#x}", self.0)
+    }
+
+    /// The memo as ASCII text, if its bytes are printable ASCII
+    /// characters followed only by zero bytes
+    pub fn to_ascii(&self) -> Option<String> {
+        let bytes = self.0.to_le_bytes();
+        let len = bytes.iter().rposition(|b| *b != 0).map_or(0, |pos| pos + 1);
+        let text = &bytes[..len];
+        if !text.is_empty() && text.iter().all(|b| b.is_ascii_graphic() || *b == b' ') {
+            Some(String::from_utf8_lossy(text).to_string())
+        } else {
+            None
+        }
+    }
+
+    /// The memo in all supported formats
+    pub fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(json!({
+            "base64": self.to_b64()?,
+            "decimal": self.0,
+            "hex": self.to_hex(),
+            "ascii": self.to_ascii(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_formats() {
+        let ascii = Memo::parse("abc", MemoFormat::Ascii).unwrap();
+        assert_eq!(
+            ascii,
+            Memo::parse("YWJjAAAAAAA=", MemoFormat::Base64).unwrap()
+        );
+        assert_eq!(ascii, Memo::parse("6513249", MemoFormat::Decimal).unwrap());
+        assert_eq!(ascii, Memo::parse("0x636261", MemoFormat::Hex).unwrap());
+        assert_eq!(ascii.to_ascii(), Some("abc".to_string()));
+        assert_eq!(ascii.to_hex(), "0x636261");
+
+        assert_eq!(Memo(0).to_ascii(), None);
+        assert_eq!(Memo(1).to_ascii(), None);
+        assert!(Memo::parse("123456789", MemoFormat::Ascii).is_err());
+        assert!(Memo::parse("AAAA", MemoFormat::Base64).is_err());
+        assert!(Memo::parse("-1", MemoFormat::Decimal).is_err());
+        assert!(Memo::parse("0x0x636261", MemoFormat::Hex).is_err());
+        assert!(Memo::parse("0x+636261", MemoFormat::Hex).is_err());
+    }
+}
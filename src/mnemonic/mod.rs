@@ -45,7 +45,7 @@ pub fn mnemonic_to_entropy(words: Vec<String>) -> Result<[u8; 32]> {
     }
 
     let mut entropy_base = [0u8; 16];
-    for (idx, matched) in RE_BYTES.find_iter(&entropy_bits).enumerate() {
+    for (idx, matched) in RE_BYTES.find_iter(entropy_bits).enumerate() {
         entropy_base[idx] = binary_to_bytes(matched.as_str()) as u8;
     }
 
@@ -58,7 +58,7 @@ pub fn mnemonic_to_entropy(words: Vec<String>) -> Result<[u8; 32]> {
 
 /// Converts a binary string into an integer
 fn binary_to_bytes(bin: &str) -> usize {
-    usize::from_str_radix(bin, 2).unwrap() as usize
+    usize::from_str_radix(bin, 2).unwrap()
 }
 
 #[cfg(test)]
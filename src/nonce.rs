@@ -0,0 +1,259 @@
+use crate::{keypair::PubKeyBin, result::Result};
+use fs2::FileExt;
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The number of seconds a reserved nonce takes precedence over the
+/// speculative nonce reported by the API. Transactions that have not
+/// shown up as pending in this time are assumed to have been dropped.
+pub const RESERVATION_TIMEOUT: u64 = 600;
+
+#[derive(Debug, Clone, Copy)]
+pub enum NonceKind {
+    /// The account nonce used for HNT and DC transactions
+    Hnt,
+    /// The nonce used for security token transactions
+    Security,
+}
+
+impl NonceKind {
+    fn key(&self) -> &'static str {
+        match self {
+            NonceKind::Hnt => "nonce",
+            NonceKind::Security => "sec_nonce",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+struct Reservation {
+    nonce: u64,
+    reserved_at: u64,
+    /// The time the reservation stops taking precedence, if it was
+    /// reserved for longer than the default timeout
+    #[serde(default)]
+    expires_at: Option<u64>,
+}
+
+impl Reservation {
+    fn expires_at(&self) -> u64 {
+        self.expires_at
+            .unwrap_or(self.reserved_at + RESERVATION_TIMEOUT)
+    }
+}
+
+/// A local store of the last nonce handed out for an address. The
+/// store file is locked while a nonce is reserved, so concurrent
+/// invocations of the wallet receive increasing nonces even before the
+/// API reports their transactions as pending.
+pub struct NonceStore {
+    path: PathBuf,
+}
+
+impl NonceStore {
+    pub fn open(dir: &Path, address: &PubKeyBin) -> Result<Self> {
+        let dir = dir.join("nonces");
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            path: dir.join(format!("{}.json", address)),
+        })
+    }
+
+    /// Reserve the next nonce of the given kind. The speculative nonce
+    /// is the latest nonce known to the API for the address. If an
+    /// explicit nonce is given it is recorded and returned as is.
+    pub fn reserve(
+        &self,
+        kind: NonceKind,
+        speculative_nonce: u64,
+        nonce: Option<u64>,
+    ) -> Result<u64> {
+        self.reserve_for(kind, speculative_nonce, nonce, RESERVATION_TIMEOUT)
+    }
+
+    /// Reserve the next nonce like `reserve`, taking precedence over
+    /// the speculative nonce for the given number of seconds. A
+    /// reservation following one that is still in effect takes
+    /// precedence for at least as long, so a nonce reserved for a
+    /// transaction that is signed elsewhere is not reused meanwhile.
+    pub fn reserve_for(
+        &self,
+        kind: NonceKind,
+        speculative_nonce: u64,
+        nonce: Option<u64>,
+        timeout: u64,
+    ) -> Result<u64> {
+        self.update(|reservations| {
+            let now = now()?;
+            let last = reservations
+                .get(kind.key())
+                .filter(|last| last.nonce > speculative_nonce && now < last.expires_at())
+                .copied();
+            let next = match (nonce, last) {
+                (Some(nonce), _) => nonce,
+                (None, Some(last)) => last.nonce + 1,
+                (None, None) => speculative_nonce + 1,
+            };
+            match reservations.get(kind.key()) {
+                Some(last) if last.nonce > next => (),
+                _ => {
+                    let expires_at = match last {
+                        Some(last) => last.expires_at().max(now + timeout),
+                        None => now + timeout,
+                    };
+                    reservations.insert(
+                        kind.key().to_string(),
+                        Reservation {
+                            nonce: next,
+                            reserved_at: now,
+                            expires_at: Some(expires_at),
+                        },
+                    );
+                }
+            }
+            Ok(next)
+        })
+    }
+
+    /// Release a reserved nonce, for example when the transaction
+    /// using it could not be submitted. Only the most recently reserved
+    /// nonce can be released.
+    pub fn release(&self, kind: NonceKind, nonce: u64) -> Result {
+        self.update(|reservations| {
+            if let Some(last) = reservations.get_mut(kind.key()) {
+                if last.nonce == nonce {
+                    last.nonce = nonce.saturating_sub(1);
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn update<F, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&mut HashMap<String, Reservation>) -> Result<R>,
+    {
+        let mut file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&self.path)?;
+        file.lock_exclusive()?;
+        let mut data = String::new();
+        file.read_to_string(&mut data)?;
+        let mut reservations: HashMap<String, Reservation> = if data.trim().is_empty() {
+            HashMap::new()
+        } else {
+            serde_json::from_str(&data)?
+        };
+        let result = f(&mut reservations)?;
+        file.seek(SeekFrom::Start(0))?;
+        file.set_len(0)?;
+        file.write_all(&serde_json::to_vec_pretty(&reservations)?)?;
+        file.unlock()?;
+        Ok(result)
+    }
+}
+
+/// A nonce for a new transaction along with the store it was reserved
+/// in, if any. A reserved nonce is released again when the reservation
+/// is dropped without being kept, for example because the transaction
+/// could not be submitted.
+pub struct NonceReservation {
+    pub nonce: u64,
+    kind: NonceKind,
+    store: Option<NonceStore>,
+}
+
+impl NonceReservation {
+    /// A nonce that is not tracked in a nonce store, for example when
+    /// previewing a transaction.
+    pub fn untracked(kind: NonceKind, nonce: u64) -> Self {
+        Self {
+            nonce,
+            kind,
+            store: None,
+        }
+    }
+
+    /// Reserve a nonce in the given store that takes precedence over
+    /// the speculative nonce for the given number of seconds.
+    pub fn reserve(
+        store: NonceStore,
+        kind: NonceKind,
+        speculative_nonce: u64,
+        nonce: Option<u64>,
+        timeout: u64,
+    ) -> Result<Self> {
+        Ok(Self {
+            nonce: store.reserve_for(kind, speculative_nonce, nonce, timeout)?,
+            kind,
+            store: Some(store),
+        })
+    }
+
+    /// Keep the reserved nonce once the transaction using it has been
+    /// submitted.
+    pub fn keep(mut self) {
+        self.store = None;
+    }
+}
+
+impl Drop for NonceReservation {
+    fn drop(&mut self) {
+        if let Some(store) = &self.store {
+            // Failing to release only leaves a gap until the
+            // reservation times out
+            let _ = store.release(self.kind, self.nonce);
+        }
+    }
+}
+
+fn now() -> Result<u64> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keypair::Keypair;
+
+    #[test]
+    fn reserve_and_release() {
+        let dir = std::env::temp_dir().join(format!("nonce-test-{}", std::process::id()));
+        let store = NonceStore::open(&dir, &Keypair::gen_keypair().pubkey_bin()).unwrap();
+
+        assert_eq!(store.reserve(NonceKind::Hnt, 4, None).unwrap(), 5);
+        assert_eq!(store.reserve(NonceKind::Hnt, 4, None).unwrap(), 6);
+        // Nonce kinds are tracked separately
+        assert_eq!(store.reserve(NonceKind::Security, 0, None).unwrap(), 1);
+        // The API catching up (or moving ahead) takes precedence
+        assert_eq!(store.reserve(NonceKind::Hnt, 8, None).unwrap(), 9);
+        // Explicit nonces are recorded
+        assert_eq!(store.reserve(NonceKind::Hnt, 8, Some(12)).unwrap(), 12);
+        assert_eq!(store.reserve(NonceKind::Hnt, 8, None).unwrap(), 13);
+        // Only the last reservation can be released
+        store.release(NonceKind::Hnt, 12).unwrap();
+        store.release(NonceKind::Hnt, 13).unwrap();
+        assert_eq!(store.reserve(NonceKind::Hnt, 8, None).unwrap(), 13);
+        // Expired reservations give way to the speculative nonce
+        assert_eq!(store.reserve_for(NonceKind::Hnt, 20, None, 0).unwrap(), 21);
+        assert_eq!(store.reserve(NonceKind::Hnt, 20, None).unwrap(), 21);
+        // A long reservation stays in effect through later short ones
+        assert_eq!(
+            store.reserve_for(NonceKind::Hnt, 30, None, 3600).unwrap(),
+            31
+        );
+        assert_eq!(store.reserve_for(NonceKind::Hnt, 30, None, 0).unwrap(), 32);
+        assert_eq!(store.reserve(NonceKind::Hnt, 30, None).unwrap(), 33);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
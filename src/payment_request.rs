@@ -0,0 +1,454 @@
+use crate::{
+    amount::Amount,
+    keypair::{Keypair, PubKeyBin, PublicKey, Verify},
+    memo::{Memo, MemoFormat},
+    result::Result,
+    traits::B58,
+};
+use helium_api::Hnt;
+use std::{
+    fmt, fs,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use url::Url;
+
+/// The scheme of payment request URIs
+pub const URI_SCHEME: &str = "helium";
+
+/// The fields of a JSON request. The "uri" field is the same request
+/// as a URI, which is included when a request is printed as JSON.
+const JSON_FIELDS: &[&str] = &[
+    "type",
+    "address",
+    "amount",
+    "memo",
+    "label",
+    "expires",
+    "signature",
+    "uri",
+];
+
+/// The encoding of request signatures
+const SIG_CONFIG: base64::Config = base64::URL_SAFE_NO_PAD;
+
+/// What a request asks the payer to do
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RequestType {
+    Payment,
+    Burn,
+}
+
+impl fmt::Display for RequestType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Payment => f.write_str("payment"),
+            Self::Burn => f.write_str("dc_burn"),
+        }
+    }
+}
+
+impl FromStr for RequestType {
+    type Err = Box<dyn std::error::Error>;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "payment" => Ok(Self::Payment),
+            "dc_burn" => Ok(Self::Burn),
+            _ => Err(format!("Unsupported request type \"{}\"", s).into()),
+        }
+    }
+}
+
+/// A request to pay, or burn for, an address. Requests can be encoded
+/// as a JSON object or as a URI of the form
+/// "helium:<address>?amount=<amount>&memo=<memo>&label=<label>&expires=<time>",
+/// with a "type=dc_burn" parameter for burn requests. All parameters
+/// are optional. The memo is base64 encoded and the expiry time is a
+/// unix time in seconds.
+///
+/// A request can be signed by the wallet of the requested address. The
+/// signature covers the URI of the request without the signature and
+/// is added as a last "sig" parameter, or a "signature" JSON field, in
+/// url safe base64. Without a valid signature anyone could have created
+/// the request. Since anyone can sign a request for their own address,
+/// a signature only verifies the requester when the payer expects the
+/// requested address.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaymentRequest {
+    pub request_type: RequestType,
+    pub address: PubKeyBin,
+    pub amount: Option<Amount>,
+    pub memo: Option<Memo>,
+    pub label: Option<String>,
+    pub expires: Option<u64>,
+    pub signature: Option<Vec<u8>>,
+}
+
+impl PaymentRequest {
+    pub fn new(request_type: RequestType, address: PubKeyBin) -> Self {
+        Self {
+            request_type,
+            address,
+            amount: None,
+            memo: None,
+            label: None,
+            expires: None,
+            signature: None,
+        }
+    }
+
+    /// Read a request given as a URI, as JSON, or as the path of a file
+    /// containing either.
+    pub fn read(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.starts_with(&format!("{}:", URI_SCHEME)) {
+            Self::from_uri(s)
+        } else if s.starts_with('{') {
+            Self::from_json(&serde_json::from_str(s)?)
+        } else {
+            let contents = fs::read_to_string(s)
+                .map_err(|err| format!("Unable to read payment request \"{}\": {}", s, err))?;
+            let contents = contents.trim();
+            if contents.starts_with('{') {
+                Self::from_json(&serde_json::from_str(contents)?)
+            } else {
+                Self::from_uri(contents)
+            }
+        }
+    }
+
+    pub fn from_uri(s: &str) -> Result<Self> {
+        let uri = Url::parse(s).map_err(|err| format!("Invalid payment request: {}", err))?;
+        if uri.scheme() != URI_SCHEME {
+            return Err(format!(
+                "Invalid payment request scheme \"{}\", expected \"{}\"",
+                uri.scheme(),
+                URI_SCHEME
+            )
+            .into());
+        }
+        let mut request = Self::new(RequestType::Payment, PubKeyBin::from_b58(uri.path())?);
+        for (key, value) in uri.query_pairs() {
+            match key.as_ref() {
+                "type" => request.request_type = value.parse()?,
+                "amount" => request.amount = Some(value.parse()?),
+                "memo" => request.memo = Some(Memo::parse(&value, MemoFormat::Base64)?),
+                "label" => request.label = Some(value.to_string()),
+                "expires" => request.expires = Some(parse_expires(&value)?),
+                "sig" => request.signature = Some(parse_signature(&value)?),
+                _ => return Err(format!("Unknown payment request parameter \"{}\"", key).into()),
+            }
+        }
+        Ok(request)
+    }
+
+    pub fn to_uri(&self) -> Result<String> {
+        self.uri(true)
+    }
+
+    fn uri(&self, signed: bool) -> Result<String> {
+        let mut uri = Url::parse(&format!("{}:{}", URI_SCHEME, self.address.to_b58()?))?;
+        {
+            let mut query = uri.query_pairs_mut();
+            if self.request_type != RequestType::Payment {
+                query.append_pair("type", &self.request_type.to_string());
+            }
+            if let Some(amount) = self.amount {
+                query.append_pair("amount", &amount_str(amount));
+            }
+            if let Some(memo) = self.memo {
+                query.append_pair("memo", &memo.to_b64()?);
+            }
+            if let Some(label) = &self.label {
+                query.append_pair("label", label);
+            }
+            if let Some(expires) = self.expires {
+                query.append_pair("expires", &expires.to_string());
+            }
+            match &self.signature {
+                Some(signature) if signed => {
+                    query.append_pair("sig", &base64::encode_config(signature, SIG_CONFIG));
+                }
+                _ => (),
+            }
+        }
+        // An empty query would otherwise leave a trailing "?"
+        if uri.query() == Some("") {
+            uri.set_query(None);
+        }
+        Ok(uri.to_string())
+    }
+
+    pub fn from_json(value: &serde_json::Value) -> Result<Self> {
+        let object = value
+            .as_object()
+            .ok_or("Invalid payment request, expected a JSON object")?;
+        let string = |key: &str| -> Result<Option<String>> {
+            match object.get(key) {
+                None | Some(serde_json::Value::Null) => Ok(None),
+                Some(serde_json::Value::String(s)) => Ok(Some(s.to_string())),
+                Some(serde_json::Value::Number(n)) => Ok(Some(n.to_string())),
+                Some(_) => Err(format!("Invalid payment request field \"{}\"", key).into()),
+            }
+        };
+        if let Some(key) = object
+            .keys()
+            .find(|key| !JSON_FIELDS.contains(&key.as_str()))
+        {
+            return Err(format!("Unknown payment request field \"{}\"", key).into());
+        }
+        let address = string("address")?.ok_or("Payment request has no address")?;
+        let mut request = Self::new(
+            match string("type")? {
+                Some(request_type) => request_type.parse()?,
+                None => RequestType::Payment,
+            },
+            PubKeyBin::from_b58(&address)?,
+        );
+        if let Some(amount) = string("amount")? {
+            request.amount = Some(amount.parse()?);
+        }
+        if let Some(memo) = string("memo")? {
+            request.memo = Some(Memo::parse(&memo, MemoFormat::Base64)?);
+        }
+        request.label = string("label")?;
+        if let Some(expires) = string("expires")? {
+            request.expires = Some(parse_expires(&expires)?);
+        }
+        if let Some(signature) = string("signature")? {
+            request.signature = Some(parse_signature(&signature)?);
+        }
+        Ok(request)
+    }
+
+    /// The request as a JSON object. Only the fields that are set are
+    /// included.
+    pub fn to_json(&self) -> Result<serde_json::Value> {
+        let mut json = json!({
+            "type": self.request_type.to_string(),
+            "address": self.address.to_b58()?,
+        });
+        if let Some(amount) = self.amount {
+            json["amount"] = amount_str(amount).into();
+        }
+        if let Some(memo) = self.memo {
+            json["memo"] = memo.to_b64()?.into();
+        }
+        if let Some(label) = &self.label {
+            json["label"] = label.clone().into();
+        }
+        if let Some(expires) = self.expires {
+            json["expires"] = expires.into();
+        }
+        if let Some(signature) = &self.signature {
+            json["signature"] = base64::encode_config(signature, SIG_CONFIG).into();
+        }
+        Ok(json)
+    }
+
+    /// Sign the request with the given keypair, which has to be the
+    /// keypair of the requested address.
+    pub fn sign(&mut self, keypair: &Keypair) -> Result {
+        if keypair.pubkey_bin() != self.address {
+            return Err(
+                "Requests can only be signed by the wallet of the requested address".into(),
+            );
+        }
+        self.signature = Some(keypair.sign(self.uri(false)?.as_bytes()));
+        Ok(())
+    }
+
+    /// Verify the signature of the request. Returns an error if the
+    /// request is not signed or the signature is not valid.
+    pub fn verify(&self) -> Result {
+        let signature = self
+            .signature
+            .as_ref()
+            .ok_or("Payment request is not signed")?;
+        let address = self.address.to_vec();
+        PublicKey::from_slice(&address[1..])
+            .ok_or("Invalid payment request address")?
+            .verify(self.uri(false)?.as_bytes(), signature)
+            .map_err(|_| "Payment request signature is invalid".into())
+    }
+
+    pub fn is_verified(&self) -> bool {
+        self.verify().is_ok()
+    }
+
+    /// Check that the request is for, and signed by, the given expected
+    /// requester.
+    pub fn check_requester(&self, expected: &PubKeyBin) -> Result {
+        if &self.address != expected {
+            return Err(format!(
+                "Payment request is from {}, not the expected requester {}",
+                self.address, expected
+            )
+            .into());
+        }
+        self.verify()
+    }
+
+    /// Returns an error if the request has expired
+    pub fn check_expiry(&self) -> Result {
+        match self.expires {
+            Some(expires) if now()? >= expires => {
+                Err(format!("Payment request expired at {}", expires).into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Key/value rows describing the request for display. The requester
+    /// is only shown as verified if the request is signed by the given
+    /// expected requester.
+    pub fn details(
+        &self,
+        expected_requester: Option<&PubKeyBin>,
+    ) -> Result<Vec<(&'static str, String)>> {
+        let mut details = vec![("Request", self.to_uri()?)];
+        if let Some(label) = &self.label {
+            details.push(("Request Label", label.clone()));
+        }
+        if let Some(amount) = self.amount {
+            details.push(("Request Amount", amount.to_string()));
+        }
+        if let Some(expires) = self.expires {
+            details.push(("Request Expires", expires.to_string()));
+        }
+        let status = match (self.signature.is_some(), expected_requester) {
+            (true, Some(expected)) if self.check_requester(expected).is_ok() => {
+                "verified requester"
+            }
+            (true, _) if self.is_verified() => "signed, requester not verified",
+            (true, _) => "invalid signature",
+            (false, _) => "unsigned, requester not verified",
+        };
+        details.push(("Requester", format!("{} ({})", self.address, status)));
+        Ok(details)
+    }
+}
+
+/// The amount in the form it is encoded in requests. HNT amounts are
+/// given without a unit, which is what wallets scanning a request
+/// expect.
+fn amount_str(amount: Amount) -> String {
+    match amount {
+        Amount::Bones(bones) => Hnt::from_bones(bones).to_string(),
+        Amount::Dc(dc) => format!("{}dc", dc),
+        Amount::Usd(usd) => format!("{}usd", usd),
+    }
+}
+
+fn parse_expires(s: &str) -> Result<u64> {
+    s.parse().map_err(|_| {
+        format!(
+            "Invalid payment request expiry \"{}\", expected a unix time",
+            s
+        )
+        .into()
+    })
+}
+
+fn parse_signature(s: &str) -> Result<Vec<u8>> {
+    base64::decode_config(s, SIG_CONFIG)
+        .map_err(|_| format!("Invalid payment request signature \"{}\"", s).into())
+}
+
+pub fn now() -> Result<u64> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keypair::Keypair;
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn uri_roundtrip() {
+        let address = Keypair::gen_keypair().pubkey_bin();
+        let mut request = PaymentRequest::new(RequestType::Payment, address);
+        let uri = request.to_uri().unwrap();
+        assert_eq!(uri, format!("helium:{}", address));
+        assert_eq!(PaymentRequest::from_uri(&uri).unwrap(), request);
+
+        request.request_type = RequestType::Burn;
+        request.amount = Some(Amount::Usd(Decimal::new(1250, 2)));
+        request.memo = Some(Memo(6513249));
+        request.label = Some("Coffee & cake".to_string());
+        request.expires = Some(1_700_000_000);
+        let uri = request.to_uri().unwrap();
+        assert!(uri.contains("amount=12.50usd"));
+        assert_eq!(PaymentRequest::from_uri(&uri).unwrap(), request);
+        assert_eq!(
+            PaymentRequest::from_json(&request.to_json().unwrap()).unwrap(),
+            request
+        );
+    }
+
+    #[test]
+    fn parse_requests() {
+        let address = Keypair::gen_keypair().pubkey_bin();
+        let request = PaymentRequest::read(&format!(
+            r#"{{"type": "payment", "address": "{}", "amount": "1.5"}}"#,
+            address
+        ))
+        .unwrap();
+        assert_eq!(request.amount, Some(Amount::Bones(150_000_000)));
+
+        let request =
+            PaymentRequest::read(&format!("helium:{}?amount=5000dc&expires=1", address)).unwrap();
+        assert_eq!(request.amount, Some(Amount::Dc(5000)));
+        assert!(request.check_expiry().is_err());
+
+        for s in &[
+            format!("bitcoin:{}", address),
+            format!("helium:{}?fee=1", address),
+            format!("helium:{}?amount=1,5", address),
+            format!("helium:{}?type=refund", address),
+            "helium:notanaddress".to_string(),
+            format!(r#"{{"address": "{}", "extra": 1}}"#, address),
+        ] {
+            assert!(PaymentRequest::read(s).is_err(), "{} should be rejected", s);
+        }
+    }
+
+    #[test]
+    fn signed_requests() {
+        let keypair = Keypair::gen_keypair();
+        let mut request = PaymentRequest::new(RequestType::Payment, keypair.pubkey_bin());
+        request.amount = Some(Amount::Bones(100));
+        request.label = Some("Invoice 1".to_string());
+        assert!(request.verify().is_err());
+        request.sign(&keypair).unwrap();
+        assert!(request.is_verified());
+
+        let uri = request.to_uri().unwrap();
+        assert!(PaymentRequest::from_uri(&uri).unwrap().is_verified());
+        let json = request.to_json().unwrap();
+        assert!(PaymentRequest::from_json(&json).unwrap().is_verified());
+
+        let tampered = uri.replace("amount=", "amount=1");
+        assert!(!PaymentRequest::from_uri(&tampered).unwrap().is_verified());
+        let mut forged = PaymentRequest::from_uri(&uri).unwrap();
+        forged.address = Keypair::gen_keypair().pubkey_bin();
+        assert!(!forged.is_verified());
+        assert!(request.sign(&Keypair::gen_keypair()).is_err());
+
+        // Only a signature by the expected requester verifies it
+        let expected = keypair.pubkey_bin();
+        let requester = |request: &PaymentRequest, expected: Option<&PubKeyBin>| {
+            request.details(expected).unwrap().last().unwrap().1.clone()
+        };
+        assert!(requester(&request, None).ends_with("(signed, requester not verified)"));
+        assert!(requester(&request, Some(&expected)).ends_with("(verified requester)"));
+        assert!(request.check_requester(&expected).is_ok());
+        let other = Keypair::gen_keypair();
+        let mut impostor = PaymentRequest::new(RequestType::Payment, other.pubkey_bin());
+        impostor.sign(&other).unwrap();
+        assert!(impostor.check_requester(&expected).is_err());
+        assert!(!requester(&impostor, Some(&expected)).contains("(verified"));
+    }
+}
@@ -0,0 +1,483 @@
+use crate::{
+    keypair::PubKeyBin,
+    result::Result,
+    traits::{ToJson, TxnHash, B58, B64},
+};
+use fs2::FileExt;
+use helium_api::{BlockchainTxn, Hnt, Txn};
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The period in seconds the daily spending limit applies to
+const DAY: u64 = 24 * 60 * 60;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PolicyFile {
+    #[serde(default)]
+    payees: Option<Vec<String>>,
+    #[serde(default)]
+    max_txn_hnt: Option<serde_json::Value>,
+    #[serde(default)]
+    max_daily_hnt: Option<serde_json::Value>,
+    #[serde(default)]
+    allowed_txns: Option<Vec<String>>,
+    #[serde(default)]
+    forbidden_txns: Vec<String>,
+    #[serde(default)]
+    hours: Option<String>,
+}
+
+/// A spending policy for a wallet. Policies are read from
+/// `policies/<address>.json` in the wallet data directory and are
+/// checked before the wallet signs a transaction.
+#[derive(Debug, Default)]
+pub struct Policy {
+    /// The addresses the wallet may pay. Any address may be paid if
+    /// not given.
+    pub payees: Option<Vec<PubKeyBin>>,
+    /// The maximum number of bones spent by a single transaction. Fees
+    /// are paid in DC and are not counted.
+    pub max_txn: Option<u64>,
+    /// The maximum number of bones spent in any 24 hour period, not
+    /// counting fees
+    pub max_daily: Option<u64>,
+    /// The transaction types the wallet may sign. Any type may be
+    /// signed if not given.
+    pub allowed_txns: Option<Vec<String>>,
+    /// The transaction types the wallet may not sign
+    pub forbidden_txns: Vec<String>,
+    /// The UTC time of day window, in minutes since midnight, in which
+    /// the wallet may sign transactions. The window wraps around
+    /// midnight if it ends before it starts.
+    pub hours: Option<(u32, u32)>,
+}
+
+fn parse_hnt(name: &str, value: &Option<serde_json::Value>) -> Result<Option<u64>> {
+    let value = match value {
+        None => return Ok(None),
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(serde_json::Value::Number(n)) => n.to_string(),
+        Some(_) => return Err(format!("Invalid {} in spending policy", name).into()),
+    };
+    let hnt = Hnt::from_str(&value)
+        .map_err(|_| format!("Invalid {} \"{}\" in spending policy", name, value))?;
+    Ok(Some(hnt.to_bones()))
+}
+
+fn parse_time(time: &str) -> Option<u32> {
+    let mut parts = time.trim().splitn(2, ':');
+    let hours: u32 = parts.next()?.parse().ok()?;
+    let minutes: u32 = parts.next()?.parse().ok()?;
+    if hours > 24 || minutes > 59 || (hours == 24 && minutes > 0) {
+        return None;
+    }
+    Some(hours * 60 + minutes)
+}
+
+/// Parse a time of day window in "HH:MM-HH:MM" format
+fn parse_hours(hours: &str) -> Result<(u32, u32)> {
+    let invalid = || format!("Invalid hours \"{}\" in spending policy", hours);
+    let mut parts = hours.splitn(2, '-');
+    let start = parts.next().and_then(parse_time).ok_or_else(invalid)?;
+    let end = parts.next().and_then(parse_time).ok_or_else(invalid)?;
+    Ok((start, end))
+}
+
+fn format_minutes(minutes: u32) -> String {
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+impl Policy {
+    fn path(dir: &Path, address: &PubKeyBin) -> PathBuf {
+        dir.join("policies").join(format!("{}.json", address))
+    }
+
+    /// Load the spending policy for the given address, if there is one
+    pub fn load(dir: &Path, address: &PubKeyBin) -> Result<Option<Self>> {
+        let path = Self::path(dir, address);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let file: PolicyFile = serde_json::from_slice(&fs::read(&path)?)
+            .map_err(|err| format!("Invalid spending policy {}: {}", path.display(), err))?;
+        let payees =
+            match file.payees {
+                Some(payees) => {
+                    let mut addresses = Vec::with_capacity(payees.len());
+                    for payee in payees {
+                        addresses.push(PubKeyBin::from_b58(&payee).map_err(|_| {
+                            format!("Invalid payee \"{}\" in spending policy", payee)
+                        })?);
+                    }
+                    Some(addresses)
+                }
+                None => None,
+            };
+        Ok(Some(Self {
+            payees,
+            max_txn: parse_hnt("max_txn_hnt", &file.max_txn_hnt)?,
+            max_daily: parse_hnt("max_daily_hnt", &file.max_daily_hnt)?,
+            allowed_txns: file.allowed_txns,
+            forbidden_txns: file.forbidden_txns,
+            hours: file.hours.as_deref().map(parse_hours).transpose()?,
+        }))
+    }
+
+    /// Check whether the given signer may sign the given transaction
+    /// at the given time, having spent the given number of bones in
+    /// the last 24 hours. Returns a description of every violation of
+    /// the policy.
+    pub fn check(
+        &self,
+        envelope: &BlockchainTxn,
+        signer: &PubKeyBin,
+        spent_today: u64,
+        now: u64,
+    ) -> Result<Vec<String>> {
+        let mut violations = vec![];
+        let txn_type = envelope.to_json()?["type"]
+            .as_str()
+            .unwrap_or("unknown")
+            .to_string();
+        if let Some(allowed) = &self.allowed_txns {
+            if !allowed.contains(&txn_type) {
+                violations.push(format!(
+                    "{} transactions are not allowed, only {}",
+                    txn_type,
+                    allowed.join(", ")
+                ));
+            }
+        }
+        if self.forbidden_txns.contains(&txn_type) {
+            violations.push(format!("{} transactions are forbidden", txn_type));
+        }
+        if let Some((start, end)) = self.hours {
+            let minute = ((now % DAY) / 60) as u32;
+            let inside = if start <= end {
+                minute >= start && minute < end
+            } else {
+                minute >= start || minute < end
+            };
+            if !inside {
+                violations.push(format!(
+                    "transactions can only be signed between {} and {} UTC",
+                    format_minutes(start),
+                    format_minutes(end)
+                ));
+            }
+        }
+
+        let spend = match spend(envelope) {
+            Some(spend) if &spend.spender == signer => spend,
+            _ => return Ok(violations),
+        };
+        if let Some(allowed) = &self.payees {
+            for payee in &spend.payees {
+                if !allowed.contains(payee) {
+                    violations.push(format!("payee {} is not in the allowed payees", payee));
+                }
+            }
+        }
+        let amount = match spend.amount {
+            Some(amount) => amount,
+            None => {
+                violations.push("total amount of the transaction is too large".to_string());
+                return Ok(violations);
+            }
+        };
+        if let Some(max) = self.max_txn {
+            if amount > max {
+                violations.push(format!(
+                    "amount of {} exceeds the per transaction limit of {}",
+                    Hnt::from_bones(amount),
+                    Hnt::from_bones(max)
+                ));
+            }
+        }
+        if let Some(max) = self.max_daily {
+            if spent_today.saturating_add(amount) > max {
+                violations.push(format!(
+                    "amount of {} with {} already spent in the last 24 hours exceeds the daily limit of {}",
+                    Hnt::from_bones(amount),
+                    Hnt::from_bones(spent_today),
+                    Hnt::from_bones(max)
+                ));
+            }
+        }
+        Ok(violations)
+    }
+}
+
+/// The HNT spent by a transaction, excluding its fee which is paid in
+/// DC
+struct Spend {
+    spender: PubKeyBin,
+    payees: Vec<PubKeyBin>,
+    /// The amount spent, or None if the amounts of the transaction add
+    /// up to more than fits in a u64
+    amount: Option<u64>,
+}
+
+fn spend(envelope: &BlockchainTxn) -> Option<Spend> {
+    let spend = |spender: &[u8], payees: Vec<&[u8]>, amount: Option<u64>| Spend {
+        spender: PubKeyBin::from_vec(spender),
+        payees: payees.into_iter().map(PubKeyBin::from_vec).collect(),
+        amount,
+    };
+    match &envelope.txn {
+        Some(Txn::Payment(t)) => Some(spend(&t.payer, vec![&t.payee], Some(t.amount))),
+        Some(Txn::PaymentV2(t)) => Some(spend(
+            &t.payer,
+            t.payments.iter().map(|p| p.payee.as_slice()).collect(),
+            t.payments
+                .iter()
+                .try_fold(0u64, |total, p| total.checked_add(p.amount)),
+        )),
+        Some(Txn::CreateHtlc(t)) => Some(spend(&t.payer, vec![&t.payee], Some(t.amount))),
+        Some(Txn::TokenBurn(t)) => Some(spend(&t.payer, vec![&t.payee], Some(t.amount))),
+        Some(Txn::TransferHotspot(t)) => {
+            Some(spend(&t.buyer, vec![&t.seller], Some(t.amount_to_seller)))
+        }
+        _ => None,
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct SpendRecord {
+    time: u64,
+    amount: u64,
+    /// The hash of the spending transaction, so it is counted once
+    /// when it is both signed and submitted
+    #[serde(default)]
+    hash: Option<String>,
+}
+
+/// A local log of the HNT spent by an address in the last 24 hours,
+/// used to enforce daily spending limits.
+pub struct SpendLog {
+    path: PathBuf,
+}
+
+impl SpendLog {
+    pub fn open(dir: &Path, address: &PubKeyBin) -> Result<Self> {
+        let dir = dir.join("policies");
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            path: dir.join(format!("{}.spent.json", address)),
+        })
+    }
+
+    /// The number of bones spent since the given time. A total too
+    /// large to represent is returned as u64::MAX so it exceeds any
+    /// limit.
+    pub fn spent_since(&self, since: u64) -> Result<u64> {
+        self.update(|records| {
+            Ok(records
+                .iter()
+                .filter(|record| record.time >= since)
+                .fold(0u64, |total, record| total.saturating_add(record.amount)))
+        })
+    }
+
+    /// Record the given number of bones as spent at the given time by
+    /// the transaction with the given hash, unless that transaction
+    /// was recorded before.
+    pub fn record(&self, time: u64, hash: &str, amount: u64) -> Result {
+        self.update(|records| {
+            records.retain(|record| record.time + DAY > time);
+            if !records
+                .iter()
+                .any(|record| record.hash.as_deref() == Some(hash))
+            {
+                records.push(SpendRecord {
+                    time,
+                    amount,
+                    hash: Some(hash.to_string()),
+                });
+            }
+            Ok(())
+        })
+    }
+
+    fn update<F, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&mut Vec<SpendRecord>) -> Result<R>,
+    {
+        let mut file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&self.path)?;
+        file.lock_exclusive()?;
+        let mut data = String::new();
+        file.read_to_string(&mut data)?;
+        let mut records: Vec<SpendRecord> = if data.trim().is_empty() {
+            vec![]
+        } else {
+            serde_json::from_str(&data)?
+        };
+        let result = f(&mut records)?;
+        file.seek(SeekFrom::Start(0))?;
+        file.set_len(0)?;
+        file.write_all(&serde_json::to_vec_pretty(&records)?)?;
+        file.unlock()?;
+        Ok(result)
+    }
+}
+
+/// Check the given transaction against the spending policy of the
+/// signer, if it has one. Returns an error explaining every violation
+/// of the policy.
+pub fn check_policy(dir: &Path, signer: &PubKeyBin, envelope: &BlockchainTxn) -> Result {
+    let policy = match Policy::load(dir, signer)? {
+        Some(policy) => policy,
+        None => return Ok(()),
+    };
+    let now = now()?;
+    let spent_today = match policy.max_daily {
+        Some(_) => SpendLog::open(dir, signer)?.spent_since(now.saturating_sub(DAY))?,
+        None => 0,
+    };
+    let violations = policy.check(envelope, signer, spent_today, now)?;
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Spending policy for {} does not allow signing this transaction:\n  {}",
+            signer.to_b58()?,
+            violations.join("\n  ")
+        )
+        .into())
+    }
+}
+
+/// Record the HNT spent by a signed or submitted transaction for the
+/// daily limit of the spender, if it has a spending policy. A
+/// transaction is only counted once, however often it is recorded.
+pub fn record_spend(dir: &Path, envelope: &BlockchainTxn) -> Result {
+    let spend = match spend(envelope) {
+        Some(spend) if spend.amount != Some(0) => spend,
+        _ => return Ok(()),
+    };
+    if Policy::load(dir, &spend.spender)?.is_none() {
+        return Ok(());
+    }
+    SpendLog::open(dir, &spend.spender)?.record(
+        now()?,
+        &envelope.txn_hash()?.to_b64_url()?,
+        spend.amount.unwrap_or(u64::MAX),
+    )
+}
+
+fn now() -> Result<u64> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keypair::Keypair, traits::TxnEnvelope};
+    use helium_api::{BlockchainTxnPaymentV2, BlockchainTxnPriceOracleV1, Payment};
+
+    fn payment(payer: PubKeyBin, payee: PubKeyBin, amount: u64) -> BlockchainTxn {
+        BlockchainTxnPaymentV2 {
+            payer: payer.into(),
+            payments: vec![Payment {
+                payee: payee.into(),
+                amount,
+            }],
+            nonce: 1,
+            fee: 0,
+            signature: vec![],
+        }
+        .in_envelope()
+    }
+
+    #[test]
+    fn check_limits_and_payees() {
+        let signer = Keypair::gen_keypair().pubkey_bin();
+        let payee = Keypair::gen_keypair().pubkey_bin();
+        let other = Keypair::gen_keypair().pubkey_bin();
+        let policy = Policy {
+            payees: Some(vec![payee]),
+            max_txn: Some(100),
+            max_daily: Some(150),
+            ..Default::default()
+        };
+        let check = |txn: &BlockchainTxn, spent| policy.check(txn, &signer, spent, 0).unwrap();
+
+        assert!(check(&payment(signer, payee, 100), 0).is_empty());
+        assert_eq!(check(&payment(signer, payee, 101), 0).len(), 1);
+        assert_eq!(check(&payment(signer, payee, 100), 51).len(), 1);
+        assert_eq!(check(&payment(signer, other, 1), 0).len(), 1);
+        // Limits only apply to transactions spent by the signer
+        assert!(check(&payment(other, other, 1_000), 1_000).is_empty());
+        // Amounts that overflow are a violation rather than wrapping
+        // around below the limits
+        let mut overflow = payment(signer, payee, u64::MAX);
+        if let Some(Txn::PaymentV2(t)) = &mut overflow.txn {
+            t.payments.push(Payment {
+                payee: payee.into(),
+                amount: 2,
+            });
+        }
+        assert_eq!(check(&overflow, 0).len(), 1);
+    }
+
+    #[test]
+    fn record_spend_once() {
+        let dir = std::env::temp_dir().join(format!("policy-test-{}", std::process::id()));
+        let signer = Keypair::gen_keypair().pubkey_bin();
+        let payee = Keypair::gen_keypair().pubkey_bin();
+        fs::create_dir_all(dir.join("policies")).unwrap();
+        fs::write(Policy::path(&dir, &signer), r#"{"max_daily_hnt": 1}"#).unwrap();
+        // Recording a transaction when it is signed and again when it
+        // is submitted counts it once
+        let txn = payment(signer, payee, 10);
+        record_spend(&dir, &txn).unwrap();
+        record_spend(&dir, &txn).unwrap();
+        record_spend(&dir, &payment(signer, payee, 5)).unwrap();
+        let spent = SpendLog::open(&dir, &signer).unwrap().spent_since(0);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(spent.unwrap(), 15);
+    }
+
+    #[test]
+    fn check_types_and_hours() {
+        let signer = Keypair::gen_keypair().pubkey_bin();
+        let oracle = BlockchainTxnPriceOracleV1 {
+            public_key: signer.into(),
+            price: 1,
+            block_height: 1,
+            signature: vec![],
+        }
+        .in_envelope();
+        let policy = Policy {
+            allowed_txns: Some(vec!["price_oracle_v1".to_string()]),
+            hours: Some(parse_hours("22:00-06:00").unwrap()),
+            ..Default::default()
+        };
+        let at = |hour: u64| hour * 60 * 60;
+        assert!(policy
+            .check(&oracle, &signer, 0, at(23))
+            .unwrap()
+            .is_empty());
+        assert!(policy.check(&oracle, &signer, 0, at(5)).unwrap().is_empty());
+        assert_eq!(policy.check(&oracle, &signer, 0, at(12)).unwrap().len(), 1);
+        let txn = payment(signer, signer, 1);
+        assert_eq!(policy.check(&txn, &signer, 0, at(23)).unwrap().len(), 1);
+
+        assert!(parse_hours("9:00-17:30").is_ok());
+        assert!(parse_hours("9-17").is_err());
+        assert!(parse_hours("25:00-17:00").is_err());
+    }
+}
@@ -0,0 +1,116 @@
+use crate::result::Result;
+use image::Luma;
+use qrcode::{render::svg, EcLevel, QrCode};
+use std::{fs, path::Path};
+use structopt::clap::arg_enum;
+
+arg_enum! {
+    /// The error correction level of a QR code. Higher levels survive
+    /// more damage but hold less data.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum QrErrorCorrection {
+        Low,
+        Medium,
+        Quartile,
+        High,
+    }
+}
+
+impl From<QrErrorCorrection> for EcLevel {
+    fn from(level: QrErrorCorrection) -> Self {
+        match level {
+            QrErrorCorrection::Low => EcLevel::L,
+            QrErrorCorrection::Medium => EcLevel::M,
+            QrErrorCorrection::Quartile => EcLevel::Q,
+            QrErrorCorrection::High => EcLevel::H,
+        }
+    }
+}
+
+/// The image formats a QR code can be written in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QrFormat {
+    Png,
+    Svg,
+}
+
+impl QrFormat {
+    /// The format for the given path, based on its file extension
+    pub fn from_path(path: &Path) -> Result<Self> {
+        match path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_lowercase)
+            .as_deref()
+        {
+            Some("png") => Ok(Self::Png),
+            Some("svg") => Ok(Self::Svg),
+            _ => Err(format!(
+                "Unsupported QR code file \"{}\", expected a .png or .svg file",
+                path.display()
+            )
+            .into()),
+        }
+    }
+}
+
+pub fn encode(data: &str, level: QrErrorCorrection) -> Result<QrCode> {
+    QrCode::with_error_correction_level(data, level.into()).map_err(|err| {
+        format!(
+            "Unable to encode {} bytes as a QR code with {} error correction: {}",
+            data.len(),
+            level.to_string().to_lowercase(),
+            err
+        )
+        .into()
+    })
+}
+
+/// Render the given data as a QR code of at least the given width and
+/// height in pixels, in the format given by the extension of the path.
+pub fn write_qr(path: &Path, data: &str, size: u32, level: QrErrorCorrection) -> Result {
+    let format = QrFormat::from_path(path)?;
+    let code = encode(data, level)?;
+    match format {
+        QrFormat::Png => code
+            .render::<Luma<u8>>()
+            .min_dimensions(size, size)
+            .build()
+            .save(path)?,
+        QrFormat::Svg => fs::write(
+            path,
+            code.render::<svg::Color>()
+                .min_dimensions(size, size)
+                .build(),
+        )?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn qr_formats() {
+        assert_eq!(
+            QrFormat::from_path(Path::new("qr.PNG")).unwrap(),
+            QrFormat::Png
+        );
+        assert_eq!(
+            QrFormat::from_path(Path::new("out/qr.svg")).unwrap(),
+            QrFormat::Svg
+        );
+        assert!(QrFormat::from_path(Path::new("qr.jpg")).is_err());
+        assert!(QrFormat::from_path(Path::new("qr")).is_err());
+    }
+
+    #[test]
+    fn error_correction_capacity() {
+        // A version 40 code holds at most 2953 bytes with low and 1273
+        // bytes with high error correction
+        let data = "a".repeat(2000);
+        assert!(encode(&data, QrErrorCorrection::Low).is_ok());
+        assert!(encode(&data, QrErrorCorrection::High).is_err());
+    }
+}
@@ -0,0 +1,172 @@
+use crate::{
+    keypair::{self, Keypair, PubKeyBin},
+    result::Result,
+};
+use byteorder::{ReadBytesExt, WriteBytesExt};
+use sodiumoxide::crypto::{sealedbox, secretbox};
+use std::io::{self, Cursor};
+
+const SEALED_VERSION_V1: u8 = 1;
+const SEALED_KEY_SIZE: usize = secretbox::KEYBYTES + sealedbox::SEALBYTES;
+
+const ARMOR_BEGIN: &str = "-----BEGIN HELIUM SEALED MESSAGE-----";
+const ARMOR_END: &str = "-----END HELIUM SEALED MESSAGE-----";
+const ARMOR_LINE_LENGTH: usize = 64;
+
+/// A message encrypted to one or more Helium addresses.
+///
+/// The message is encrypted with a random symmetric key. That key is
+/// then sealed to the X25519 equivalent of the ed25519 key of each
+/// recipient, so any one of the recipients can open the message
+/// without revealing who else it was sent to. The sealed keys do not
+/// name their recipient, so opening a message tries each of them.
+pub struct SealedMessage {
+    sealed_keys: Vec<Vec<u8>>,
+    nonce: secretbox::Nonce,
+    ciphertext: Vec<u8>,
+}
+
+impl SealedMessage {
+    pub fn seal(message: &[u8], recipients: &[PubKeyBin]) -> Result<Self> {
+        let recipients = unique_recipients(recipients);
+        if recipients.is_empty() {
+            return Err("At least one recipient expected".into());
+        }
+        if recipients.len() > u8::MAX as usize {
+            return Err(format!("Too many recipients {}", recipients.len()).into());
+        }
+        keypair::init();
+        let key = secretbox::gen_key();
+        let nonce = secretbox::gen_nonce();
+        let mut sealed_keys = Vec::with_capacity(recipients.len());
+        for recipient in recipients {
+            sealed_keys.push(sealedbox::seal(&key.0, &recipient.to_curve25519()?));
+        }
+        Ok(Self {
+            sealed_keys,
+            nonce,
+            ciphertext: secretbox::seal(message, &nonce, &key),
+        })
+    }
+
+    pub fn open(&self, keypair: &Keypair) -> Result<Vec<u8>> {
+        let pubkey_bin = keypair.pubkey_bin();
+        let public_key = pubkey_bin.to_curve25519()?;
+        let secret_key = keypair.to_curve25519()?;
+        let key_data = self
+            .sealed_keys
+            .iter()
+            .find_map(|sealed_key| sealedbox::open(sealed_key, &public_key, &secret_key).ok())
+            .ok_or_else(|| format!("Message is not addressed to {}", pubkey_bin))?;
+        let key = secretbox::Key::from_slice(&key_data).ok_or("Invalid message key")?;
+        let message = secretbox::open(&self.ciphertext, &self.nonce, &key)
+            .map_err(|_| "Failed to decrypt message")?;
+        Ok(message)
+    }
+
+    /// The number of recipients the message was sealed to
+    pub fn recipient_count(&self) -> usize {
+        self.sealed_keys.len()
+    }
+
+    pub fn read(reader: &mut dyn io::Read) -> Result<Self> {
+        let version = reader.read_u8()?;
+        if version != SEALED_VERSION_V1 {
+            return Err(format!("Invalid sealed message version {}", version).into());
+        }
+        let count = reader.read_u8()?;
+        let mut sealed_keys = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let mut sealed_key = vec![0; SEALED_KEY_SIZE];
+            reader.read_exact(&mut sealed_key)?;
+            sealed_keys.push(sealed_key);
+        }
+        let mut nonce = secretbox::Nonce([0; secretbox::NONCEBYTES]);
+        reader.read_exact(&mut nonce.0)?;
+        let mut ciphertext = vec![];
+        reader.read_to_end(&mut ciphertext)?;
+        Ok(Self {
+            sealed_keys,
+            nonce,
+            ciphertext,
+        })
+    }
+
+    pub fn write(&self, writer: &mut dyn io::Write) -> Result {
+        writer.write_u8(SEALED_VERSION_V1)?;
+        writer.write_u8(self.sealed_keys.len() as u8)?;
+        for sealed_key in &self.sealed_keys {
+            writer.write_all(sealed_key)?;
+        }
+        writer.write_all(&self.nonce.0)?;
+        writer.write_all(&self.ciphertext)?;
+        Ok(())
+    }
+
+    /// Encode the message as base64 text between begin and end
+    /// markers so it can be pasted into an email or chat message.
+    pub fn to_armor(&self) -> Result<String> {
+        let mut buf = vec![];
+        self.write(&mut buf)?;
+        let encoded = base64::encode(&buf);
+        let mut lines = vec![ARMOR_BEGIN.to_string()];
+        for chunk in encoded.as_bytes().chunks(ARMOR_LINE_LENGTH) {
+            lines.push(String::from_utf8(chunk.to_vec())?);
+        }
+        lines.push(ARMOR_END.to_string());
+        Ok(lines.join("\n"))
+    }
+
+    pub fn from_armor(armor: &str) -> Result<Self> {
+        let armor = armor.trim();
+        let body = armor
+            .strip_prefix(ARMOR_BEGIN)
+            .and_then(|s| s.strip_suffix(ARMOR_END))
+            .ok_or("Invalid sealed message armor")?;
+        let encoded: String = body.split_whitespace().collect();
+        let decoded = base64::decode(&encoded)?;
+        Self::read(&mut Cursor::new(decoded))
+    }
+}
+
+/// The given recipients in order with duplicates removed
+pub fn unique_recipients(recipients: &[PubKeyBin]) -> Vec<PubKeyBin> {
+    let mut unique: Vec<PubKeyBin> = Vec::with_capacity(recipients.len());
+    for recipient in recipients {
+        if !unique.contains(recipient) {
+            unique.push(*recipient);
+        }
+    }
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip_multiple_recipients() {
+        let alice = Keypair::gen_keypair();
+        let bob = Keypair::gen_keypair();
+        let message = b"shard data";
+        let sealed = SealedMessage::seal(
+            message,
+            &[alice.pubkey_bin(), bob.pubkey_bin(), alice.pubkey_bin()],
+        )
+        .expect("sealed message");
+        let armor = sealed.to_armor().expect("armor");
+        assert!(!armor.contains(&alice.pubkey_bin().to_string()));
+        let decoded = SealedMessage::from_armor(&armor).expect("decoded armor");
+        assert_eq!(decoded.recipient_count(), 2);
+        assert_eq!(decoded.open(&alice).expect("alice open"), message);
+        assert_eq!(decoded.open(&bob).expect("bob open"), message);
+    }
+
+    #[test]
+    fn reject_other_recipient() {
+        let alice = Keypair::gen_keypair();
+        let mallory = Keypair::gen_keypair();
+        let sealed = SealedMessage::seal(b"secret", &[alice.pubkey_bin()]).expect("sealed");
+        assert!(sealed.open(&mallory).is_err());
+    }
+}
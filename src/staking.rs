@@ -56,7 +56,7 @@ impl Client {
         response["data"]["publicAddress"]
             .as_str()
             .map_or(Err("Invalid staking address from server".into()), |v| {
-                PubKeyBin::from_b58(&v)
+                PubKeyBin::from_b58(v)
             })
     }
 
@@ -77,6 +77,6 @@ impl Client {
         let txn_data = response["data"]["transaction"]
             .as_str()
             .ok_or("Unexpected transaction response from staking server")?;
-        Ok(BlockchainTxn::from_b64(txn_data)?)
+        BlockchainTxn::from_b64(txn_data)
     }
 }
@@ -44,7 +44,7 @@ impl B64 for BlockchainTxn {
 
 impl B64 for u64 {
     fn to_b64_config(&self, config: base64::Config) -> Result<String> {
-        Ok(base64::encode_config(&self.to_le_bytes(), config))
+        Ok(base64::encode_config(self.to_le_bytes(), config))
     }
 
     fn from_b64_config(b64: &str, config: base64::Config) -> Result<Self> {
@@ -56,7 +56,7 @@ impl B64 for u64 {
 
 impl B64 for Vec<u8> {
     fn to_b64_config(&self, config: base64::Config) -> Result<String> {
-        Ok(base64::encode_config(&self, config))
+        Ok(base64::encode_config(self, config))
     }
 
     fn from_b64_config(b64: &str, config: base64::Config) -> Result<Self> {
@@ -3,7 +3,14 @@ use crate::{
     result::Result,
     traits::{B58, B64},
 };
-use helium_api::{BlockchainTxnTransferHotspotV1, BlockchainTxnVarsV1, BlockchainVarV1};
+use helium_api::{
+    blockchain_txn_routing_v1::Update, BlockchainStateChannelSummaryV1, BlockchainTxn,
+    BlockchainTxnAddGatewayV1, BlockchainTxnAssertLocationV1, BlockchainTxnCreateHtlcV1,
+    BlockchainTxnOuiV1, BlockchainTxnPaymentV1, BlockchainTxnPaymentV2, BlockchainTxnPriceOracleV1,
+    BlockchainTxnRedeemHtlcV1, BlockchainTxnRoutingV1, BlockchainTxnSecurityExchangeV1,
+    BlockchainTxnStateChannelCloseV1, BlockchainTxnStateChannelOpenV1, BlockchainTxnTokenBurnV1,
+    BlockchainTxnTransferHotspotV1, BlockchainTxnVarsV1, BlockchainVarV1, Hnt, Hst, Payment, Txn,
+};
 
 pub(crate) fn maybe_b58(data: &[u8]) -> Result<Option<String>> {
     if data.is_empty() {
@@ -21,6 +28,21 @@ pub(crate) fn maybe_b64_url(data: &[u8]) -> Result<Option<String>> {
     }
 }
 
+pub(crate) fn pubkey_b58(data: &[u8]) -> Result<String> {
+    if data.len() != 33 {
+        return Err(format!("Invalid public key length {}", data.len()).into());
+    }
+    PubKeyBin::from_vec(data).to_b58()
+}
+
+pub(crate) fn maybe_pubkey_b58(data: &[u8]) -> Result<Option<String>> {
+    if data.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(pubkey_b58(data)?))
+    }
+}
+
 pub trait ToJson {
     fn to_json(&self) -> Result<serde_json::Value>;
 }
@@ -114,12 +136,292 @@ impl ToJson for BlockchainTxnTransferHotspotV1 {
         let buyer = PubKeyBin::from_vec(&self.buyer).to_b58()?;
 
         Ok(json!({
+            "type": "transfer_hotspot_v1",
             "seller": seller,
             "gateway": gateway,
             "buyer": buyer,
             "amount_to_seller": self.amount_to_seller,
+            "amount_to_seller_hnt": Hnt::from_bones(self.amount_to_seller),
             "buyer_nonce": self.buyer_nonce,
-            "fee": self.fee
+            "fee": self.fee,
+            "seller_signature": maybe_b64_url(&self.seller_signature)?,
+            "buyer_signature": maybe_b64_url(&self.buyer_signature)?,
+        }))
+    }
+}
+
+impl ToJson for BlockchainTxnPaymentV1 {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(json!({
+            "type": "payment_v1",
+            "payer": pubkey_b58(&self.payer)?,
+            "payee": pubkey_b58(&self.payee)?,
+            "amount": Hnt::from_bones(self.amount),
+            "fee": self.fee,
+            "nonce": self.nonce,
+            "signature": maybe_b64_url(&self.signature)?,
+        }))
+    }
+}
+
+impl ToJson for Payment {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(json!({
+            "payee": pubkey_b58(&self.payee)?,
+            "amount": Hnt::from_bones(self.amount),
+        }))
+    }
+}
+
+impl ToJson for BlockchainTxnPaymentV2 {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(json!({
+            "type": "payment_v2",
+            "payer": pubkey_b58(&self.payer)?,
+            "payments": self.payments.to_json()?,
+            "fee": self.fee,
+            "nonce": self.nonce,
+            "signature": maybe_b64_url(&self.signature)?,
         }))
     }
 }
+
+impl ToJson for BlockchainTxnCreateHtlcV1 {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(json!({
+            "type": "create_htlc_v1",
+            "payer": pubkey_b58(&self.payer)?,
+            "payee": pubkey_b58(&self.payee)?,
+            "address": pubkey_b58(&self.address)?,
+            "hashlock": hex::encode(&self.hashlock),
+            "timelock": self.timelock,
+            "amount": Hnt::from_bones(self.amount),
+            "fee": self.fee,
+            "nonce": self.nonce,
+            "signature": maybe_b64_url(&self.signature)?,
+        }))
+    }
+}
+
+impl ToJson for BlockchainTxnRedeemHtlcV1 {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(json!({
+            "type": "redeem_htlc_v1",
+            "payee": pubkey_b58(&self.payee)?,
+            "address": pubkey_b58(&self.address)?,
+            "preimage": String::from_utf8_lossy(&self.preimage),
+            "fee": self.fee,
+            "signature": maybe_b64_url(&self.signature)?,
+        }))
+    }
+}
+
+impl ToJson for BlockchainTxnOuiV1 {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(json!({
+            "type": "oui_v1",
+            "owner": pubkey_b58(&self.owner)?,
+            "payer": maybe_pubkey_b58(&self.payer)?,
+            "oui": self.oui,
+            "addresses": vec_to_b58s(&self.addresses)?,
+            "filter": self.filter.to_b64()?,
+            "requested_subnet_size": self.requested_subnet_size,
+            "staking_fee": self.staking_fee,
+            "fee": self.fee,
+            "owner_signature": maybe_b64_url(&self.owner_signature)?,
+            "payer_signature": maybe_b64_url(&self.payer_signature)?,
+        }))
+    }
+}
+
+impl ToJson for BlockchainTxnAddGatewayV1 {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(json!({
+            "type": "add_gateway_v1",
+            "owner": pubkey_b58(&self.owner)?,
+            "gateway": pubkey_b58(&self.gateway)?,
+            "payer": maybe_pubkey_b58(&self.payer)?,
+            "staking_fee": self.staking_fee,
+            "fee": self.fee,
+            "owner_signature": maybe_b64_url(&self.owner_signature)?,
+            "gateway_signature": maybe_b64_url(&self.gateway_signature)?,
+            "payer_signature": maybe_b64_url(&self.payer_signature)?,
+        }))
+    }
+}
+
+impl ToJson for BlockchainTxnAssertLocationV1 {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(json!({
+            "type": "assert_location_v1",
+            "owner": pubkey_b58(&self.owner)?,
+            "gateway": pubkey_b58(&self.gateway)?,
+            "payer": maybe_pubkey_b58(&self.payer)?,
+            "location": self.location,
+            "nonce": self.nonce,
+            "staking_fee": self.staking_fee,
+            "fee": self.fee,
+            "owner_signature": maybe_b64_url(&self.owner_signature)?,
+            "gateway_signature": maybe_b64_url(&self.gateway_signature)?,
+            "payer_signature": maybe_b64_url(&self.payer_signature)?,
+        }))
+    }
+}
+
+impl ToJson for BlockchainTxnTokenBurnV1 {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(json!({
+            "type": "token_burn_v1",
+            "payer": pubkey_b58(&self.payer)?,
+            "payee": pubkey_b58(&self.payee)?,
+            "amount": Hnt::from_bones(self.amount),
+            "memo": self.memo.to_b64()?,
+            "fee": self.fee,
+            "nonce": self.nonce,
+            "signature": maybe_b64_url(&self.signature)?,
+        }))
+    }
+}
+
+impl ToJson for BlockchainTxnSecurityExchangeV1 {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(json!({
+            "type": "security_exchange_v1",
+            "payer": pubkey_b58(&self.payer)?,
+            "payee": pubkey_b58(&self.payee)?,
+            "amount": Hst::from_bones(self.amount),
+            "fee": self.fee,
+            "nonce": self.nonce,
+            "signature": maybe_b64_url(&self.signature)?,
+        }))
+    }
+}
+
+impl ToJson for BlockchainTxnPriceOracleV1 {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(json!({
+            "type": "price_oracle_v1",
+            "public_key": pubkey_b58(&self.public_key)?,
+            "price": self.price,
+            "block_height": self.block_height,
+            "signature": maybe_b64_url(&self.signature)?,
+        }))
+    }
+}
+
+impl ToJson for Update {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(match self {
+            Update::UpdateRouters(update) => json!({
+                "action": "update_routers",
+                "addresses": vec_to_b58s(&update.router_addresses)?,
+            }),
+            Update::NewXor(filter) => json!({
+                "action": "new_xor",
+                "filter": filter.to_b64()?,
+            }),
+            Update::UpdateXor(update) => json!({
+                "action": "update_xor",
+                "index": update.index,
+                "filter": update.filter.to_b64()?,
+            }),
+            Update::RequestSubnet(size) => json!({
+                "action": "request_subnet",
+                "subnet_size": size,
+            }),
+        })
+    }
+}
+
+impl ToJson for BlockchainTxnRoutingV1 {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        let update = match &self.update {
+            Some(update) => update.to_json()?,
+            None => json!(null),
+        };
+        Ok(json!({
+            "type": "routing_v1",
+            "oui": self.oui,
+            "owner": pubkey_b58(&self.owner)?,
+            "update": update,
+            "staking_fee": self.staking_fee,
+            "fee": self.fee,
+            "nonce": self.nonce,
+            "signature": maybe_b64_url(&self.signature)?,
+        }))
+    }
+}
+
+impl ToJson for BlockchainTxnStateChannelOpenV1 {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(json!({
+            "type": "state_channel_open_v1",
+            "id": maybe_b64_url(&self.id)?,
+            "owner": pubkey_b58(&self.owner)?,
+            "oui": self.oui,
+            "amount": self.amount,
+            "expire_within": self.expire_within,
+            "fee": self.fee,
+            "nonce": self.nonce,
+            "signature": maybe_b64_url(&self.signature)?,
+        }))
+    }
+}
+
+impl ToJson for BlockchainStateChannelSummaryV1 {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(json!({
+            "client": pubkey_b58(&self.client_pubkeybin)?,
+            "num_packets": self.num_packets,
+            "num_dcs": self.num_dcs,
+        }))
+    }
+}
+
+impl ToJson for BlockchainTxnStateChannelCloseV1 {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        let state_channel = match &self.state_channel {
+            Some(sc) => json!({
+                "id": maybe_b64_url(&sc.id)?,
+                "owner": pubkey_b58(&sc.owner)?,
+                "nonce": sc.nonce,
+                "credits": sc.credits,
+                "summaries": sc.summaries.to_json()?,
+                "root_hash": maybe_b64_url(&sc.root_hash)?,
+                "state": sc.state,
+                "expire_at_block": sc.expire_at_block,
+            }),
+            None => json!(null),
+        };
+        Ok(json!({
+            "type": "state_channel_close_v1",
+            "closer": pubkey_b58(&self.closer)?,
+            "state_channel": state_channel,
+            "signature": maybe_b64_url(&self.signature)?,
+        }))
+    }
+}
+
+impl ToJson for BlockchainTxn {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        match &self.txn {
+            Some(Txn::Payment(t)) => t.to_json(),
+            Some(Txn::PaymentV2(t)) => t.to_json(),
+            Some(Txn::CreateHtlc(t)) => t.to_json(),
+            Some(Txn::RedeemHtlc(t)) => t.to_json(),
+            Some(Txn::Oui(t)) => t.to_json(),
+            Some(Txn::AddGateway(t)) => t.to_json(),
+            Some(Txn::AssertLocation(t)) => t.to_json(),
+            Some(Txn::TokenBurn(t)) => t.to_json(),
+            Some(Txn::SecurityExchange(t)) => t.to_json(),
+            Some(Txn::PriceOracleSubmission(t)) => t.to_json(),
+            Some(Txn::Routing(t)) => t.to_json(),
+            Some(Txn::StateChannelOpen(t)) => t.to_json(),
+            Some(Txn::StateChannelClose(t)) => t.to_json(),
+            Some(Txn::Vars(t)) => t.to_json(),
+            Some(Txn::TransferHotspot(t)) => t.to_json(),
+            Some(_) => Err("Unsupported transaction type".into()),
+            None => Err("Empty transaction".into()),
+        }
+    }
+}
@@ -5,7 +5,11 @@ pub use self::read_write::ReadWrite;
 pub use self::sign::Sign;
 pub use self::txn_envelope::TxnEnvelope;
 pub use self::txn_fee::{TxnFee, TxnFeeConfig, TxnStakingFee};
+pub use self::txn_hash::TxnHash;
 pub use self::txn_payer::TxnPayer;
+pub use self::txn_sign::TxnSign;
+pub use self::txn_validate::TxnValidate;
+pub use self::txn_verify::TxnVerify;
 
 pub mod b58;
 pub mod b64;
@@ -14,4 +18,8 @@ pub mod read_write;
 pub mod sign;
 pub mod txn_envelope;
 pub mod txn_fee;
+pub mod txn_hash;
 pub mod txn_payer;
+pub mod txn_sign;
+pub mod txn_validate;
+pub mod txn_verify;
@@ -3,34 +3,36 @@ use crate::result::Result;
 use helium_api::{
     BlockchainTxnAddGatewayV1, BlockchainTxnAssertLocationV1, BlockchainTxnCreateHtlcV1,
     BlockchainTxnOuiV1, BlockchainTxnPaymentV1, BlockchainTxnPaymentV2, BlockchainTxnPriceOracleV1,
-    BlockchainTxnRedeemHtlcV1, BlockchainTxnSecurityExchangeV1, BlockchainTxnTokenBurnV1,
+    BlockchainTxnRedeemHtlcV1, BlockchainTxnRoutingV1, BlockchainTxnSecurityExchangeV1,
+    BlockchainTxnStateChannelCloseV1, BlockchainTxnStateChannelOpenV1, BlockchainTxnTokenBurnV1,
     BlockchainTxnTransferHotspotV1, BlockchainTxnVarsV1, Message,
 };
 
 pub trait Sign: Message + std::clone::Clone {
-    fn sign(&self, keypair: &Keypair) -> Result<Vec<u8>>
-    where
-        Self: std::marker::Sized;
-    fn verify(&self, pubkey: &PublicKey, signature: &[u8]) -> Result;
+    /// A copy of the transaction with all signatures cleared, which is
+    /// what gets signed and hashed
+    fn unsigned(&self) -> Self;
+
+    fn sign(&self, keypair: &Keypair) -> Result<Vec<u8>> {
+        let mut buf = vec![];
+        self.unsigned().encode(&mut buf)?;
+        Ok(keypair.sign(&buf))
+    }
+
+    fn verify(&self, pubkey: &PublicKey, signature: &[u8]) -> Result {
+        let mut buf = vec![];
+        self.unsigned().encode(&mut buf)?;
+        pubkey.verify(&buf, signature)
+    }
 }
 
 macro_rules! impl_sign {
     ($txn_type:ty, $( $sig: ident ),+ ) => {
         impl Sign for $txn_type {
-            fn sign(&self, keypair: &Keypair) -> Result<Vec<u8>> {
-                let mut buf = vec![];
-                let mut txn = self.clone();
-                $(txn.$sig = vec![];)+
-                txn.encode(& mut buf)?;
-                Ok(keypair.sign(&buf))
-            }
-
-            fn verify(&self, pubkey: &PublicKey, signature: &[u8]) -> Result {
-                let mut buf = vec![];
+            fn unsigned(&self) -> Self {
                 let mut txn = self.clone();
                 $(txn.$sig = vec![];)+
-                txn.encode(& mut buf)?;
-                pubkey.verify(&buf, &signature)
+                txn
             }
         }
     }
@@ -68,3 +70,6 @@ impl_sign!(
     buyer_signature,
     seller_signature
 );
+impl_sign!(BlockchainTxnRoutingV1, signature);
+impl_sign!(BlockchainTxnStateChannelOpenV1, signature);
+impl_sign!(BlockchainTxnStateChannelCloseV1, signature);
@@ -1,14 +1,15 @@
 use super::TxnEnvelope;
 use crate::result::Result;
 use helium_api::{
-    BlockchainTxnAddGatewayV1, BlockchainTxnAssertLocationV1, BlockchainTxnCreateHtlcV1,
-    BlockchainTxnOuiV1, BlockchainTxnPaymentV1, BlockchainTxnPaymentV2, BlockchainTxnRedeemHtlcV1,
-    BlockchainTxnSecurityExchangeV1, BlockchainTxnTokenBurnV1, BlockchainTxnTransferHotspotV1,
-    Message,
+    BlockchainTxn, BlockchainTxnAddGatewayV1, BlockchainTxnAssertLocationV1,
+    BlockchainTxnCreateHtlcV1, BlockchainTxnOuiV1, BlockchainTxnPaymentV1, BlockchainTxnPaymentV2,
+    BlockchainTxnRedeemHtlcV1, BlockchainTxnSecurityExchangeV1, BlockchainTxnTokenBurnV1,
+    BlockchainTxnTransferHotspotV1, Message, Txn,
 };
 use serde_derive::{Deserialize, Serialize};
+use std::convert::TryFrom;
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
 pub struct TxnFeeConfig {
     // whether transaction fees are active
     txn_fees: bool,
@@ -62,13 +63,31 @@ fn calculate_txn_fee(payload_size: usize, config: &TxnFeeConfig) -> u64 {
     if payload_size <= dc_payload_size {
         1
     } else {
-        // integer div/ceil from: https://stackoverflow.com/a/2745086
-        ((payload_size + dc_payload_size - 1) / dc_payload_size) as u64
+        // integer div/ceil, payload_size is known to be at least 1
+        ((payload_size - 1) / dc_payload_size + 1) as u64
     }
 }
 
 const TXN_FEE_SIGNATURE_SIZE: usize = 64;
 
+/// The number of data credits per USD
+pub const DC_PER_USD: u64 = 100_000;
+/// Oracle prices are given in 1/100_000_000 USD per HNT
+pub const ORACLE_PRICE_SCALE: u64 = 100_000_000;
+const BONES_PER_HNT: u64 = 100_000_000;
+
+/// Convert a DC amount to the number of bones that need to be burned
+/// for it at the given oracle price, rounded up.
+pub fn dc_to_bones(dc: u64, oracle_price: u64) -> Result<u64> {
+    if oracle_price == 0 {
+        return Err("Invalid oracle price".into());
+    }
+    let numerator = dc as u128 * BONES_PER_HNT as u128 * ORACLE_PRICE_SCALE as u128;
+    let denominator = DC_PER_USD as u128 * oracle_price as u128;
+    u64::try_from(numerator.div_ceil(denominator))
+        .map_err(|_| format!("{} DC is too large to convert to HNT", dc).into())
+}
+
 macro_rules! payer_sig_clear {
     (basic, $txn:ident) => {};
     (payer, $txn:ident) => {
@@ -146,6 +165,36 @@ impl TxnStakingFee for BlockchainTxnOuiV1 {
     }
 }
 
+impl TxnFee for BlockchainTxn {
+    fn txn_fee(&self, config: &TxnFeeConfig) -> Result<u64> {
+        match &self.txn {
+            Some(Txn::Payment(t)) => t.txn_fee(config),
+            Some(Txn::PaymentV2(t)) => t.txn_fee(config),
+            Some(Txn::CreateHtlc(t)) => t.txn_fee(config),
+            Some(Txn::RedeemHtlc(t)) => t.txn_fee(config),
+            Some(Txn::SecurityExchange(t)) => t.txn_fee(config),
+            Some(Txn::TokenBurn(t)) => t.txn_fee(config),
+            Some(Txn::AddGateway(t)) => t.txn_fee(config),
+            Some(Txn::AssertLocation(t)) => t.txn_fee(config),
+            Some(Txn::Oui(t)) => t.txn_fee(config),
+            Some(Txn::TransferHotspot(t)) => t.txn_fee(config),
+            _ => Err("Unsupported transaction for fee calculation".into()),
+        }
+    }
+}
+
+impl TxnStakingFee for BlockchainTxn {
+    /// Transactions without a staking fee have a staking fee of zero.
+    fn txn_staking_fee(&self, config: &TxnFeeConfig) -> Result<u64> {
+        match &self.txn {
+            Some(Txn::AddGateway(t)) => t.txn_staking_fee(config),
+            Some(Txn::AssertLocation(t)) => t.txn_staking_fee(config),
+            Some(Txn::Oui(t)) => t.txn_staking_fee(config),
+            _ => Ok(0),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -184,6 +233,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn dc_bones_conversion() {
+        // At $1 per HNT one DC is 1/100_000 HNT
+        assert_eq!(dc_to_bones(35_000, ORACLE_PRICE_SCALE).unwrap(), 35_000_000);
+        assert_eq!(
+            dc_to_bones(35_000, 10 * ORACLE_PRICE_SCALE).unwrap(),
+            3_500_000
+        );
+        // Partial bones are rounded up
+        assert_eq!(dc_to_bones(1, 3 * ORACLE_PRICE_SCALE).unwrap(), 334);
+        assert!(dc_to_bones(1, 0).is_err());
+        assert!(dc_to_bones(u64::MAX, 1).is_err());
+    }
+
     #[test]
     fn payment_v1_fee() {
         let payer = Keypair::gen_keypair();
@@ -0,0 +1,71 @@
+use super::Sign;
+use crate::result::Result;
+use helium_api::{BlockchainTxn, Txn};
+use sha2::{Digest, Sha256};
+
+/// The hash of a transaction as computed by the blockchain: the
+/// SHA-256 digest of the encoded transaction with all signatures
+/// cleared. The hash is therefore known before the transaction is
+/// signed or submitted. It is implemented for every transaction that
+/// can be signed.
+pub trait TxnHash {
+    fn txn_hash(&self) -> Result<Vec<u8>>;
+}
+
+impl<T: Sign> TxnHash for T {
+    fn txn_hash(&self) -> Result<Vec<u8>> {
+        let mut buf = vec![];
+        self.unsigned().encode(&mut buf)?;
+        Ok(Sha256::digest(&buf).to_vec())
+    }
+}
+
+impl TxnHash for BlockchainTxn {
+    fn txn_hash(&self) -> Result<Vec<u8>> {
+        match &self.txn {
+            Some(Txn::PriceOracleSubmission(t)) => t.txn_hash(),
+            Some(Txn::Payment(t)) => t.txn_hash(),
+            Some(Txn::PaymentV2(t)) => t.txn_hash(),
+            Some(Txn::CreateHtlc(t)) => t.txn_hash(),
+            Some(Txn::RedeemHtlc(t)) => t.txn_hash(),
+            Some(Txn::AddGateway(t)) => t.txn_hash(),
+            Some(Txn::AssertLocation(t)) => t.txn_hash(),
+            Some(Txn::Oui(t)) => t.txn_hash(),
+            Some(Txn::SecurityExchange(t)) => t.txn_hash(),
+            Some(Txn::TokenBurn(t)) => t.txn_hash(),
+            Some(Txn::Vars(t)) => t.txn_hash(),
+            Some(Txn::TransferHotspot(t)) => t.txn_hash(),
+            Some(Txn::Routing(t)) => t.txn_hash(),
+            Some(Txn::StateChannelOpen(t)) => t.txn_hash(),
+            Some(Txn::StateChannelClose(t)) => t.txn_hash(),
+            _ => Err("Unsupported transaction for hashing".into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keypair::Keypair, traits::TxnEnvelope};
+    use helium_api::BlockchainTxnPaymentV1;
+
+    #[test]
+    fn hash_ignores_signature() {
+        let payer = Keypair::gen_keypair();
+        let mut txn = BlockchainTxnPaymentV1 {
+            payee: Keypair::gen_keypair().pubkey_bin().into(),
+            payer: payer.pubkey_bin().into(),
+            amount: 10_000,
+            nonce: 1,
+            fee: 0,
+            signature: vec![],
+        };
+        let unsigned_hash = txn.txn_hash().unwrap();
+        txn.signature = txn.sign(&payer).unwrap();
+        assert_eq!(unsigned_hash, txn.txn_hash().unwrap());
+        assert_eq!(unsigned_hash, txn.in_envelope().txn_hash().unwrap());
+
+        txn.nonce = 2;
+        assert_ne!(unsigned_hash, txn.txn_hash().unwrap());
+    }
+}
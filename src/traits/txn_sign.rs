@@ -0,0 +1,112 @@
+use crate::{keypair::Keypair, result::Result, traits::Sign};
+use helium_api::{BlockchainTxn, Txn};
+
+pub trait TxnSign {
+    /// Sign every role in the transaction that is expected to be
+    /// signed by the given keypair and return the roles that were
+    /// signed.
+    fn sign_roles(&mut self, keypair: &Keypair) -> Result<Vec<&'static str>>;
+}
+
+macro_rules! sign_role {
+    ($roles:ident, $keypair:ident, $txn:ident, $role:expr, $signer:ident, $sig:ident) => {
+        let signer: Vec<u8> = $keypair.pubkey_bin().into();
+        if $txn.$signer == signer {
+            $txn.$sig = $txn.sign($keypair)?;
+            $roles.push($role);
+        }
+    };
+}
+
+impl TxnSign for BlockchainTxn {
+    fn sign_roles(&mut self, keypair: &Keypair) -> Result<Vec<&'static str>> {
+        let mut roles = vec![];
+        match &mut self.txn {
+            Some(Txn::Payment(t)) => {
+                sign_role!(roles, keypair, t, "payer", payer, signature);
+            }
+            Some(Txn::PaymentV2(t)) => {
+                sign_role!(roles, keypair, t, "payer", payer, signature);
+            }
+            Some(Txn::CreateHtlc(t)) => {
+                sign_role!(roles, keypair, t, "payer", payer, signature);
+            }
+            Some(Txn::RedeemHtlc(t)) => {
+                sign_role!(roles, keypair, t, "payee", payee, signature);
+            }
+            Some(Txn::TokenBurn(t)) => {
+                sign_role!(roles, keypair, t, "payer", payer, signature);
+            }
+            Some(Txn::SecurityExchange(t)) => {
+                sign_role!(roles, keypair, t, "payer", payer, signature);
+            }
+            Some(Txn::PriceOracleSubmission(t)) => {
+                sign_role!(roles, keypair, t, "oracle", public_key, signature);
+            }
+            Some(Txn::Routing(t)) => {
+                sign_role!(roles, keypair, t, "owner", owner, signature);
+            }
+            Some(Txn::StateChannelOpen(t)) => {
+                sign_role!(roles, keypair, t, "owner", owner, signature);
+            }
+            Some(Txn::StateChannelClose(t)) => {
+                sign_role!(roles, keypair, t, "closer", closer, signature);
+            }
+            Some(Txn::Oui(t)) => {
+                sign_role!(roles, keypair, t, "owner", owner, owner_signature);
+                sign_role!(roles, keypair, t, "payer", payer, payer_signature);
+            }
+            Some(Txn::AddGateway(t)) => {
+                sign_role!(roles, keypair, t, "owner", owner, owner_signature);
+                sign_role!(roles, keypair, t, "gateway", gateway, gateway_signature);
+                sign_role!(roles, keypair, t, "payer", payer, payer_signature);
+            }
+            Some(Txn::AssertLocation(t)) => {
+                sign_role!(roles, keypair, t, "owner", owner, owner_signature);
+                sign_role!(roles, keypair, t, "gateway", gateway, gateway_signature);
+                sign_role!(roles, keypair, t, "payer", payer, payer_signature);
+            }
+            Some(Txn::TransferHotspot(t)) => {
+                sign_role!(roles, keypair, t, "seller", seller, seller_signature);
+                sign_role!(roles, keypair, t, "buyer", buyer, buyer_signature);
+            }
+            _ => return Err("Unsupported transaction for signing".into()),
+        };
+        Ok(roles)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::{TxnEnvelope, TxnVerify};
+    use helium_api::BlockchainTxnTransferHotspotV1;
+
+    #[test]
+    fn sign_matching_roles() {
+        let seller = Keypair::gen_keypair();
+        let buyer = Keypair::gen_keypair();
+        let mut envelope = BlockchainTxnTransferHotspotV1 {
+            gateway: Keypair::gen_keypair().pubkey_bin().into(),
+            seller: seller.pubkey_bin().into(),
+            buyer: buyer.pubkey_bin().into(),
+            seller_signature: vec![],
+            buyer_signature: vec![],
+            buyer_nonce: 1,
+            amount_to_seller: 0,
+            fee: 0,
+        }
+        .in_envelope();
+
+        assert!(envelope
+            .sign_roles(&Keypair::gen_keypair())
+            .unwrap()
+            .is_empty());
+        assert_eq!(envelope.sign_roles(&buyer).unwrap(), vec!["buyer"]);
+        assert!(envelope.verify_signature("buyer").is_ok());
+        assert!(envelope.verify_signature("seller").is_err());
+        assert_eq!(envelope.sign_roles(&seller).unwrap(), vec!["seller"]);
+        assert!(envelope.verify_signature("buyer").is_ok());
+        assert!(envelope.verify_signature("seller").is_ok());
+    }
+}
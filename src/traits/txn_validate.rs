@@ -0,0 +1,465 @@
+use crate::{keypair::PubKeyBin, result::Result};
+use helium_api::{
+    BlockchainTxn, BlockchainTxnAddGatewayV1, BlockchainTxnAssertLocationV1,
+    BlockchainTxnCreateHtlcV1, BlockchainTxnOuiV1, BlockchainTxnPaymentV1, BlockchainTxnPaymentV2,
+    BlockchainTxnRedeemHtlcV1, BlockchainTxnSecurityExchangeV1, BlockchainTxnTokenBurnV1,
+    BlockchainTxnTransferHotspotV1, Hnt, Hst, Txn,
+};
+
+/// The minimum and maximum requested subnet size for an OUI
+const MIN_SUBNET_SIZE: u32 = 8;
+const MAX_SUBNET_SIZE: u32 = 65_536;
+/// The size of a SHA256 hashlock
+const HASHLOCK_SIZE: usize = 32;
+
+/// The state of the account paying for a transaction
+#[derive(Debug, Clone, Default)]
+pub struct PayerState {
+    pub balance: u64,
+    pub dc_balance: u64,
+    pub sec_balance: u64,
+    /// The last nonce used by the account, including pending
+    /// transactions
+    pub nonce: u64,
+    /// The last security nonce used by the account, including pending
+    /// transactions
+    pub sec_nonce: u64,
+    /// The bones burned to pay for the transaction fee. This is zero
+    /// if the account has enough DC for the fee.
+    pub fee_bones: u64,
+}
+
+pub trait TxnValidate {
+    /// The account paying for the transaction
+    fn fee_payer(&self) -> Result<PubKeyBin>;
+    /// The fee of the transaction in DC, including any staking fee
+    fn total_fee(&self) -> u64;
+    /// Check the transaction against the state of the paying account
+    /// and return a description of every problem that would make the
+    /// transaction fail.
+    fn validate(&self, payer: &PayerState) -> Vec<String>;
+}
+
+fn check_amount(problems: &mut Vec<String>, amount: u64) {
+    if amount == 0 {
+        problems.push("amount must be greater than zero".to_string());
+    }
+}
+
+/// The address of the given key for display, which may be malformed in
+/// a transaction that was not built by this wallet
+fn address(key: &[u8]) -> String {
+    PubKeyBin::try_from_vec(key)
+        .map(|key| key.to_string())
+        .unwrap_or_else(|_| "<invalid address>".to_string())
+}
+
+fn check_payee(problems: &mut Vec<String>, payer: &[u8], payee: &[u8]) {
+    if PubKeyBin::try_from_vec(payee).is_err() {
+        problems.push("payee is not a valid address".to_string());
+    } else if payer == payee {
+        problems.push(format!("payee {} is the same as the payer", address(payee)));
+    }
+}
+
+fn check_nonce(problems: &mut Vec<String>, nonce: u64, last_nonce: u64) {
+    if nonce <= last_nonce {
+        problems.push(format!(
+            "nonce {} has already been used, the next nonce is {}",
+            nonce,
+            last_nonce + 1
+        ));
+    } else if nonce > last_nonce + 1 {
+        problems.push(format!(
+            "nonce {} leaves a gap, the next nonce is {} and the transaction will not clear until the nonces before it are used",
+            nonce,
+            last_nonce + 1
+        ));
+    }
+}
+
+fn check_balance(problems: &mut Vec<String>, payer: &PayerState, amount: u64) {
+    let needed = amount.saturating_add(payer.fee_bones);
+    if payer.balance < needed {
+        problems.push(format!(
+            "insufficient balance, {} HNT is needed for the amount and fee but the account has {} HNT and {} DC",
+            Hnt::from_bones(needed),
+            Hnt::from_bones(payer.balance),
+            payer.dc_balance
+        ));
+    }
+}
+
+fn payer_or(payer: &[u8], owner: &[u8]) -> Result<PubKeyBin> {
+    if payer.is_empty() {
+        PubKeyBin::try_from_vec(owner)
+    } else {
+        PubKeyBin::try_from_vec(payer)
+    }
+}
+
+impl TxnValidate for BlockchainTxnPaymentV1 {
+    fn fee_payer(&self) -> Result<PubKeyBin> {
+        PubKeyBin::try_from_vec(&self.payer)
+    }
+
+    fn total_fee(&self) -> u64 {
+        self.fee
+    }
+
+    fn validate(&self, payer: &PayerState) -> Vec<String> {
+        let mut problems = vec![];
+        check_amount(&mut problems, self.amount);
+        check_payee(&mut problems, &self.payer, &self.payee);
+        check_nonce(&mut problems, self.nonce, payer.nonce);
+        check_balance(&mut problems, payer, self.amount);
+        problems
+    }
+}
+
+impl TxnValidate for BlockchainTxnPaymentV2 {
+    fn fee_payer(&self) -> Result<PubKeyBin> {
+        PubKeyBin::try_from_vec(&self.payer)
+    }
+
+    fn total_fee(&self) -> u64 {
+        self.fee
+    }
+
+    fn validate(&self, payer: &PayerState) -> Vec<String> {
+        let mut problems = vec![];
+        if self.payments.is_empty() {
+            problems.push("no payments given".to_string());
+        }
+        for (index, payment) in self.payments.iter().enumerate() {
+            check_amount(&mut problems, payment.amount);
+            check_payee(&mut problems, &self.payer, &payment.payee);
+            if self.payments[..index]
+                .iter()
+                .any(|p| p.payee == payment.payee)
+            {
+                problems.push(format!(
+                    "payee {} is paid more than once",
+                    address(&payment.payee)
+                ));
+            }
+        }
+        check_nonce(&mut problems, self.nonce, payer.nonce);
+        let amount = self
+            .payments
+            .iter()
+            .fold(0u64, |sum, p| sum.saturating_add(p.amount));
+        check_balance(&mut problems, payer, amount);
+        problems
+    }
+}
+
+impl TxnValidate for BlockchainTxnTokenBurnV1 {
+    fn fee_payer(&self) -> Result<PubKeyBin> {
+        PubKeyBin::try_from_vec(&self.payer)
+    }
+
+    fn total_fee(&self) -> u64 {
+        self.fee
+    }
+
+    fn validate(&self, payer: &PayerState) -> Vec<String> {
+        let mut problems = vec![];
+        check_amount(&mut problems, self.amount);
+        check_nonce(&mut problems, self.nonce, payer.nonce);
+        check_balance(&mut problems, payer, self.amount);
+        problems
+    }
+}
+
+impl TxnValidate for BlockchainTxnCreateHtlcV1 {
+    fn fee_payer(&self) -> Result<PubKeyBin> {
+        PubKeyBin::try_from_vec(&self.payer)
+    }
+
+    fn total_fee(&self) -> u64 {
+        self.fee
+    }
+
+    fn validate(&self, payer: &PayerState) -> Vec<String> {
+        let mut problems = vec![];
+        check_amount(&mut problems, self.amount);
+        check_payee(&mut problems, &self.payer, &self.payee);
+        if self.hashlock.len() != HASHLOCK_SIZE {
+            problems.push(format!(
+                "hashlock must be a {} byte SHA256 digest",
+                HASHLOCK_SIZE
+            ));
+        }
+        check_nonce(&mut problems, self.nonce, payer.nonce);
+        check_balance(&mut problems, payer, self.amount);
+        problems
+    }
+}
+
+impl TxnValidate for BlockchainTxnRedeemHtlcV1 {
+    fn fee_payer(&self) -> Result<PubKeyBin> {
+        PubKeyBin::try_from_vec(&self.payee)
+    }
+
+    fn total_fee(&self) -> u64 {
+        self.fee
+    }
+
+    fn validate(&self, payer: &PayerState) -> Vec<String> {
+        let mut problems = vec![];
+        if self.preimage.is_empty() {
+            problems.push("preimage must not be empty".to_string());
+        }
+        check_balance(&mut problems, payer, 0);
+        problems
+    }
+}
+
+impl TxnValidate for BlockchainTxnSecurityExchangeV1 {
+    fn fee_payer(&self) -> Result<PubKeyBin> {
+        PubKeyBin::try_from_vec(&self.payer)
+    }
+
+    fn total_fee(&self) -> u64 {
+        self.fee
+    }
+
+    fn validate(&self, payer: &PayerState) -> Vec<String> {
+        let mut problems = vec![];
+        check_amount(&mut problems, self.amount);
+        check_payee(&mut problems, &self.payer, &self.payee);
+        check_nonce(&mut problems, self.nonce, payer.sec_nonce);
+        if payer.sec_balance < self.amount {
+            problems.push(format!(
+                "insufficient security balance, {} HST is needed but the account has {} HST",
+                Hst::from_bones(self.amount),
+                Hst::from_bones(payer.sec_balance)
+            ));
+        }
+        check_balance(&mut problems, payer, 0);
+        problems
+    }
+}
+
+impl TxnValidate for BlockchainTxnOuiV1 {
+    fn fee_payer(&self) -> Result<PubKeyBin> {
+        payer_or(&self.payer, &self.owner)
+    }
+
+    fn total_fee(&self) -> u64 {
+        self.fee + self.staking_fee
+    }
+
+    fn validate(&self, payer: &PayerState) -> Vec<String> {
+        let mut problems = vec![];
+        let size = self.requested_subnet_size;
+        if !size.is_power_of_two() || !(MIN_SUBNET_SIZE..=MAX_SUBNET_SIZE).contains(&size) {
+            problems.push(format!(
+                "subnet size {} must be a power of two between {} and {}",
+                size, MIN_SUBNET_SIZE, MAX_SUBNET_SIZE
+            ));
+        }
+        check_balance(&mut problems, payer, 0);
+        problems
+    }
+}
+
+impl TxnValidate for BlockchainTxnAddGatewayV1 {
+    fn fee_payer(&self) -> Result<PubKeyBin> {
+        payer_or(&self.payer, &self.owner)
+    }
+
+    fn total_fee(&self) -> u64 {
+        self.fee + self.staking_fee
+    }
+
+    fn validate(&self, payer: &PayerState) -> Vec<String> {
+        let mut problems = vec![];
+        check_balance(&mut problems, payer, 0);
+        problems
+    }
+}
+
+impl TxnValidate for BlockchainTxnAssertLocationV1 {
+    fn fee_payer(&self) -> Result<PubKeyBin> {
+        payer_or(&self.payer, &self.owner)
+    }
+
+    fn total_fee(&self) -> u64 {
+        self.fee + self.staking_fee
+    }
+
+    fn validate(&self, payer: &PayerState) -> Vec<String> {
+        let mut problems = vec![];
+        check_balance(&mut problems, payer, 0);
+        problems
+    }
+}
+
+impl TxnValidate for BlockchainTxnTransferHotspotV1 {
+    fn fee_payer(&self) -> Result<PubKeyBin> {
+        PubKeyBin::try_from_vec(&self.buyer)
+    }
+
+    fn total_fee(&self) -> u64 {
+        self.fee
+    }
+
+    fn validate(&self, payer: &PayerState) -> Vec<String> {
+        let mut problems = vec![];
+        if self.buyer == self.seller {
+            problems.push("buyer is the same as the seller".to_string());
+        }
+        check_nonce(&mut problems, self.buyer_nonce, payer.nonce);
+        check_balance(&mut problems, payer, self.amount_to_seller);
+        problems
+    }
+}
+
+macro_rules! dispatch {
+    ($txn:expr, $t:ident => $call:expr, $unsupported:expr) => {
+        match &$txn.txn {
+            Some(Txn::Payment($t)) => $call,
+            Some(Txn::PaymentV2($t)) => $call,
+            Some(Txn::TokenBurn($t)) => $call,
+            Some(Txn::CreateHtlc($t)) => $call,
+            Some(Txn::RedeemHtlc($t)) => $call,
+            Some(Txn::SecurityExchange($t)) => $call,
+            Some(Txn::Oui($t)) => $call,
+            Some(Txn::AddGateway($t)) => $call,
+            Some(Txn::AssertLocation($t)) => $call,
+            Some(Txn::TransferHotspot($t)) => $call,
+            _ => $unsupported,
+        }
+    };
+}
+
+/// Whether the given transaction is one that can be validated
+pub fn is_supported(envelope: &BlockchainTxn) -> bool {
+    dispatch!(envelope, _t => true, false)
+}
+
+impl TxnValidate for BlockchainTxn {
+    fn fee_payer(&self) -> Result<PubKeyBin> {
+        dispatch!(self, t => t.fee_payer(), Err("Unsupported transaction for validation".into()))
+    }
+
+    fn total_fee(&self) -> u64 {
+        dispatch!(self, t => t.total_fee(), 0)
+    }
+
+    fn validate(&self, payer: &PayerState) -> Vec<String> {
+        dispatch!(self, t => t.validate(payer), vec![])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keypair::Keypair;
+    use helium_api::Payment;
+
+    fn payer_state() -> PayerState {
+        PayerState {
+            balance: 1_000,
+            dc_balance: 0,
+            nonce: 4,
+            fee_bones: 100,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn validate_payment() {
+        let payer = Keypair::gen_keypair().pubkey_bin();
+        let payee = Keypair::gen_keypair().pubkey_bin();
+        let mut txn = BlockchainTxnPaymentV2 {
+            payer: payer.into(),
+            payments: vec![Payment {
+                payee: payee.into(),
+                amount: 900,
+            }],
+            nonce: 5,
+            fee: 0,
+            signature: vec![],
+        };
+        assert!(txn.validate(&payer_state()).is_empty());
+
+        // Amount and fee exceed the balance
+        txn.payments[0].amount = 901;
+        assert_eq!(txn.validate(&payer_state()).len(), 1);
+
+        txn.payments[0].amount = 0;
+        txn.payments.push(Payment {
+            payee: payer.into(),
+            amount: 1,
+        });
+        txn.nonce = 4;
+        // Zero amount, payee equal to payer and a used nonce
+        assert_eq!(txn.validate(&payer_state()).len(), 3);
+    }
+
+    #[test]
+    fn validate_malformed_keys() {
+        let payer = Keypair::gen_keypair().pubkey_bin();
+        let mut txn = BlockchainTxnPaymentV2 {
+            payer: vec![1, 2, 3],
+            payments: vec![Payment {
+                payee: vec![1, 2, 3],
+                amount: 900,
+            }],
+            nonce: 5,
+            fee: 0,
+            signature: vec![],
+        };
+        assert!(txn.fee_payer().is_err());
+
+        txn.payer = payer.into();
+        assert!(txn.fee_payer().is_ok());
+        assert_eq!(txn.validate(&payer_state()).len(), 1);
+    }
+
+    #[test]
+    fn validate_nonce_gaps() {
+        let buyer = Keypair::gen_keypair().pubkey_bin();
+        let seller = Keypair::gen_keypair().pubkey_bin();
+        let mut txn = BlockchainTxnTransferHotspotV1 {
+            gateway: vec![],
+            seller: seller.into(),
+            buyer: buyer.into(),
+            seller_signature: vec![],
+            buyer_signature: vec![],
+            buyer_nonce: 5,
+            amount_to_seller: 100,
+            fee: 0,
+        };
+        assert!(txn.validate(&payer_state()).is_empty());
+        for nonce in &[4, 7] {
+            txn.buyer_nonce = *nonce;
+            assert_eq!(txn.validate(&payer_state()).len(), 1);
+        }
+    }
+
+    #[test]
+    fn validate_oui_subnet_size() {
+        let owner = Keypair::gen_keypair().pubkey_bin();
+        let mut txn = BlockchainTxnOuiV1 {
+            owner: owner.into(),
+            payer: vec![],
+            filter: vec![],
+            addresses: vec![],
+            staking_fee: 0,
+            requested_subnet_size: 8,
+            fee: 0,
+            oui: 1,
+            owner_signature: vec![],
+            payer_signature: vec![],
+        };
+        assert!(txn.validate(&payer_state()).is_empty());
+        for size in &[0, 4, 12, 131_072] {
+            txn.requested_subnet_size = *size;
+            assert_eq!(txn.validate(&payer_state()).len(), 1);
+        }
+    }
+}
@@ -0,0 +1,311 @@
+use crate::{
+    keypair::{PubKeyBin, PublicKey},
+    result::Result,
+    traits::{Sign, B58},
+};
+use helium_api::{BlockchainTxn, Txn};
+use std::fmt;
+
+/// The outcome of checking a single signature in a transaction.
+pub enum SignatureStatus {
+    Valid,
+    Invalid,
+    Missing,
+    /// The signature could not be matched to any known signing key
+    UnknownSigner,
+}
+
+pub struct SignatureCheck {
+    /// The role of the signer in the transaction, e.g. "payer"
+    pub role: &'static str,
+    pub signer: Option<PubKeyBin>,
+    pub status: SignatureStatus,
+}
+
+/// The keys that currently control chain variables, as set on the
+/// chain.
+#[derive(Debug, Default)]
+pub struct ChainKeys {
+    pub master_key: Option<PubKeyBin>,
+    pub multi_keys: Vec<PubKeyBin>,
+}
+
+impl ChainKeys {
+    /// Read the chain keys from the given chain variables, where the
+    /// master key and the multisig keys are given as addresses.
+    pub fn from_vars(vars: &serde_json::Map<String, serde_json::Value>) -> Result<Self> {
+        let key = |value: &serde_json::Value| -> Result<PubKeyBin> {
+            value
+                .as_str()
+                .and_then(|address| PubKeyBin::from_b58(address).ok())
+                .ok_or_else(|| format!("Invalid chain key {}", value).into())
+        };
+        let master_key = vars.get("master_key").map(key).transpose()?;
+        let mut multi_keys = vec![];
+        if let Some(keys) = vars.get("multi_keys") {
+            for value in keys.as_array().ok_or("Invalid chain multi_keys")? {
+                multi_keys.push(key(value)?);
+            }
+        }
+        Ok(Self {
+            master_key,
+            multi_keys,
+        })
+    }
+}
+
+pub trait TxnVerify {
+    /// Check every signature in the transaction against the address
+    /// it is expected to be signed by.
+    fn verify_signatures(&self) -> Result<Vec<SignatureCheck>> {
+        self.verify_signatures_with(None)
+    }
+
+    /// Check every signature like `verify_signatures`. Proofs in chain
+    /// variable transactions by the current master or multisig keys
+    /// can only be checked with the given chain keys, and are reported
+    /// as having an unknown signer without them.
+    fn verify_signatures_with(&self, chain_keys: Option<&ChainKeys>)
+        -> Result<Vec<SignatureCheck>>;
+
+    /// Check that the signature for the given role is present and
+    /// valid.
+    fn verify_signature(&self, role: &str) -> Result {
+        match self
+            .verify_signatures()?
+            .into_iter()
+            .find(|check| check.role == role)
+        {
+            Some(SignatureCheck {
+                status: SignatureStatus::Valid,
+                ..
+            }) => Ok(()),
+            Some(check) => Err(format!("{} signature is {}", role, check.status).into()),
+            None => Err(format!("No {} signature in transaction", role).into()),
+        }
+    }
+}
+
+fn signer_key(signer: &[u8]) -> Option<PublicKey> {
+    if signer.len() != 33 {
+        return None;
+    }
+    PublicKey::from_slice(&signer[1..])
+}
+
+fn check<T: Sign>(txn: &T, role: &'static str, signer: &[u8], signature: &[u8]) -> SignatureCheck {
+    let status = match signer_key(signer) {
+        _ if signature.is_empty() => SignatureStatus::Missing,
+        Some(pubkey) if txn.verify(&pubkey, signature).is_ok() => SignatureStatus::Valid,
+        _ => SignatureStatus::Invalid,
+    };
+    SignatureCheck {
+        role,
+        signer: signer_key(signer).map(|pubkey| PubKeyBin::from(&pubkey)),
+        status,
+    }
+}
+
+fn maybe_check<T: Sign>(
+    checks: &mut Vec<SignatureCheck>,
+    txn: &T,
+    role: &'static str,
+    signer: &[u8],
+    signature: &[u8],
+) {
+    if !signer.is_empty() {
+        checks.push(check(txn, role, signer, signature))
+    }
+}
+
+fn check_any<T: Sign>(
+    txn: &T,
+    role: &'static str,
+    signers: &[Vec<u8>],
+    signature: &[u8],
+) -> SignatureCheck {
+    signers
+        .iter()
+        .map(|signer| check(txn, role, signer, signature))
+        .find(|check| matches!(check.status, SignatureStatus::Valid))
+        .unwrap_or(SignatureCheck {
+            role,
+            signer: None,
+            status: SignatureStatus::UnknownSigner,
+        })
+}
+
+impl TxnVerify for BlockchainTxn {
+    fn verify_signatures_with(
+        &self,
+        chain_keys: Option<&ChainKeys>,
+    ) -> Result<Vec<SignatureCheck>> {
+        let mut checks = vec![];
+        match &self.txn {
+            Some(Txn::Payment(t)) => checks.push(check(t, "payer", &t.payer, &t.signature)),
+            Some(Txn::PaymentV2(t)) => checks.push(check(t, "payer", &t.payer, &t.signature)),
+            Some(Txn::CreateHtlc(t)) => checks.push(check(t, "payer", &t.payer, &t.signature)),
+            Some(Txn::RedeemHtlc(t)) => checks.push(check(t, "payee", &t.payee, &t.signature)),
+            Some(Txn::TokenBurn(t)) => checks.push(check(t, "payer", &t.payer, &t.signature)),
+            Some(Txn::SecurityExchange(t)) => {
+                checks.push(check(t, "payer", &t.payer, &t.signature))
+            }
+            Some(Txn::PriceOracleSubmission(t)) => {
+                checks.push(check(t, "oracle", &t.public_key, &t.signature))
+            }
+            Some(Txn::Routing(t)) => checks.push(check(t, "owner", &t.owner, &t.signature)),
+            Some(Txn::StateChannelOpen(t)) => {
+                checks.push(check(t, "owner", &t.owner, &t.signature))
+            }
+            Some(Txn::StateChannelClose(t)) => {
+                checks.push(check(t, "closer", &t.closer, &t.signature))
+            }
+            Some(Txn::Oui(t)) => {
+                checks.push(check(t, "owner", &t.owner, &t.owner_signature));
+                maybe_check(&mut checks, t, "payer", &t.payer, &t.payer_signature);
+            }
+            Some(Txn::AddGateway(t)) => {
+                checks.push(check(t, "owner", &t.owner, &t.owner_signature));
+                checks.push(check(t, "gateway", &t.gateway, &t.gateway_signature));
+                maybe_check(&mut checks, t, "payer", &t.payer, &t.payer_signature);
+            }
+            Some(Txn::AssertLocation(t)) => {
+                checks.push(check(t, "owner", &t.owner, &t.owner_signature));
+                checks.push(check(t, "gateway", &t.gateway, &t.gateway_signature));
+                maybe_check(&mut checks, t, "payer", &t.payer, &t.payer_signature);
+            }
+            Some(Txn::TransferHotspot(t)) => {
+                checks.push(check(t, "seller", &t.seller, &t.seller_signature));
+                checks.push(check(t, "buyer", &t.buyer, &t.buyer_signature));
+            }
+            Some(Txn::Vars(t)) => {
+                // Proofs authorize the transaction and are made by the
+                // keys currently on chain, while key proofs show
+                // possession of the keys the transaction sets.
+                let master_keys: Vec<Vec<u8>> = chain_keys
+                    .and_then(|keys| keys.master_key)
+                    .into_iter()
+                    .map(|key| key.into())
+                    .collect();
+                let multi_keys: Vec<Vec<u8>> = chain_keys
+                    .map(|keys| keys.multi_keys.iter().map(|key| (*key).into()).collect())
+                    .unwrap_or_default();
+                if !t.proof.is_empty() {
+                    checks.push(check_any(t, "proof", &master_keys, &t.proof));
+                }
+                maybe_check(&mut checks, t, "master_key", &t.master_key, &t.key_proof);
+                for proof in &t.multi_key_proofs {
+                    checks.push(check_any(t, "multi_key_proof", &t.multi_keys, proof));
+                }
+                for proof in &t.multi_proofs {
+                    checks.push(check_any(t, "multi_proof", &multi_keys, proof));
+                }
+            }
+            _ => return Err("Unsupported transaction for verification".into()),
+        };
+        Ok(checks)
+    }
+}
+
+impl fmt::Display for SignatureStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            SignatureStatus::Valid => "valid",
+            SignatureStatus::Invalid => "invalid",
+            SignatureStatus::Missing => "missing",
+            SignatureStatus::UnknownSigner => "unknown signer",
+        };
+        f.write_str(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keypair::Keypair, traits::TxnEnvelope};
+    use helium_api::{BlockchainTxnTransferHotspotV1, BlockchainTxnVarsV1};
+
+    #[test]
+    fn verify_transfer_hotspot() {
+        let seller = Keypair::gen_keypair();
+        let buyer = Keypair::gen_keypair();
+        let mut txn = BlockchainTxnTransferHotspotV1 {
+            gateway: Keypair::gen_keypair().pubkey_bin().into(),
+            seller: seller.pubkey_bin().into(),
+            buyer: buyer.pubkey_bin().into(),
+            seller_signature: vec![],
+            buyer_signature: vec![],
+            buyer_nonce: 1,
+            amount_to_seller: 0,
+            fee: 0,
+        };
+        txn.seller_signature = txn.sign(&seller).unwrap();
+        let envelope = txn.in_envelope();
+        assert!(envelope.verify_signature("seller").is_ok());
+        assert!(envelope.verify_signature("buyer").is_err());
+
+        // A signature by the wrong key does not verify
+        txn.buyer_signature = txn.sign(&seller).unwrap();
+        let envelope = txn.in_envelope();
+        assert!(envelope.verify_signature("buyer").is_err());
+
+        txn.buyer_signature = txn.sign(&buyer).unwrap();
+        let envelope = txn.in_envelope();
+        assert!(envelope.verify_signature("seller").is_ok());
+        assert!(envelope.verify_signature("buyer").is_ok());
+    }
+
+    #[test]
+    fn verify_vars_against_chain_keys() {
+        let chain_key = Keypair::gen_keypair();
+        let new_key = Keypair::gen_keypair();
+        let mut txn = BlockchainTxnVarsV1 {
+            version_predicate: 0,
+            master_key: vec![],
+            proof: vec![],
+            key_proof: vec![],
+            vars: vec![],
+            nonce: 1,
+            unsets: vec![],
+            cancels: vec![],
+            multi_key_proofs: vec![],
+            multi_proofs: vec![],
+            multi_keys: vec![new_key.pubkey_bin().into()],
+        };
+        txn.multi_key_proofs = vec![txn.sign(&new_key).unwrap()];
+        txn.multi_proofs = vec![txn.sign(&chain_key).unwrap(), txn.sign(&new_key).unwrap()];
+        let envelope = txn.in_envelope();
+        let statuses = |chain_keys: Option<&ChainKeys>| -> Vec<String> {
+            envelope
+                .verify_signatures_with(chain_keys)
+                .unwrap()
+                .iter()
+                .map(|check| format!("{} {}", check.role, check.status))
+                .collect()
+        };
+
+        // Without the chain keys only the key proofs can be checked
+        assert_eq!(
+            statuses(None),
+            vec![
+                "multi_key_proof valid",
+                "multi_proof unknown signer",
+                "multi_proof unknown signer"
+            ]
+        );
+        // Proofs by keys that are only set by the transaction do not
+        // authorize it
+        let chain_keys = ChainKeys {
+            master_key: None,
+            multi_keys: vec![chain_key.pubkey_bin()],
+        };
+        assert_eq!(
+            statuses(Some(&chain_keys)),
+            vec![
+                "multi_key_proof valid",
+                "multi_proof valid",
+                "multi_proof unknown signer"
+            ]
+        );
+    }
+}